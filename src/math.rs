@@ -1 +1,2 @@
 pub mod nullspace;
+pub mod simplex;
@@ -0,0 +1,323 @@
+use std::iter::repeat_n;
+
+use malachite::{
+    num::basic::traits::{One, Zero},
+    Rational,
+};
+use thiserror::Error;
+
+/// A linear constraint of the form `coefficients · x <relation> rhs`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Constraint {
+    pub coefficients: Vec<Rational>,
+    pub relation: Relation,
+    pub rhs: Rational,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relation {
+    LessEqual,
+    Equal,
+    GreaterEqual,
+}
+
+impl Relation {
+    fn flipped(self) -> Self {
+        match self {
+            Self::LessEqual => Self::GreaterEqual,
+            Self::Equal => Self::Equal,
+            Self::GreaterEqual => Self::LessEqual,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Error)]
+pub enum SimplexError {
+    #[error("the constraints cannot be satisfied")]
+    Infeasible,
+    #[error("the objective can be increased indefinitely")]
+    Unbounded,
+}
+
+/// Maximizes `objective · x` subject to all `constraints` and `x >= 0`.
+///
+/// Uses the two-phase simplex method with exact arithmetic and Bland's rule to prevent cycling.
+///
+/// # Panics
+///
+/// Panics if any constraint does not have exactly one coefficient per variable.
+pub fn maximize(
+    objective: &[Rational],
+    constraints: &[Constraint],
+) -> Result<Vec<Rational>, SimplexError> {
+    let variables = objective.len();
+    let slacks = constraints
+        .iter()
+        .filter(|constraint| constraint.relation != Relation::Equal)
+        .count();
+    let artificials_start = variables + slacks;
+
+    let mut tableau = Tableau::default();
+    let mut slack = variables;
+    let mut artificial = artificials_start;
+    for constraint in constraints {
+        assert_eq!(constraint.coefficients.len(), variables);
+
+        let (sign, relation) = if constraint.rhs < 0 {
+            (-Rational::ONE, constraint.relation.flipped())
+        } else {
+            (Rational::ONE, constraint.relation)
+        };
+
+        let mut row = constraint
+            .coefficients
+            .iter()
+            .map(|coefficient| coefficient * &sign)
+            .collect::<Vec<_>>();
+        row.extend(repeat_n(Rational::ZERO, slacks));
+
+        let basis = match relation {
+            Relation::LessEqual => {
+                row[slack] = Rational::ONE;
+                slack += 1;
+                slack - 1
+            }
+            Relation::Equal => {
+                artificial += 1;
+                artificial - 1
+            }
+            Relation::GreaterEqual => {
+                row[slack] = -Rational::ONE;
+                slack += 1;
+                artificial += 1;
+                artificial - 1
+            }
+        };
+
+        tableau.rows.push(row);
+        tableau.rhs.push(&constraint.rhs * sign);
+        tableau.basis.push(basis);
+    }
+
+    let columns = artificial;
+    for (row, &basis) in tableau.rows.iter_mut().zip(&tableau.basis) {
+        row.resize(columns, Rational::ZERO);
+        row[basis] = Rational::ONE;
+    }
+
+    // Phase 1: Find a feasible solution by driving all artificial variables to zero.
+    let phase_1_costs = (0..columns)
+        .map(|column| {
+            if column < artificials_start {
+                Rational::ZERO
+            } else {
+                -Rational::ONE
+            }
+        })
+        .collect::<Vec<_>>();
+    tableau.optimize(&phase_1_costs, columns)?;
+
+    let infeasibility = tableau
+        .basis
+        .iter()
+        .zip(&tableau.rhs)
+        .filter(|(&basis, _)| basis >= artificials_start)
+        .map(|(_, rhs)| rhs)
+        .sum::<Rational>();
+    if infeasibility != 0 {
+        return Err(SimplexError::Infeasible);
+    }
+
+    for row_index in 0..tableau.rows.len() {
+        if tableau.basis[row_index] >= artificials_start {
+            if let Some(column) =
+                (0..artificials_start).find(|&column| tableau.rows[row_index][column] != 0)
+            {
+                tableau.pivot(row_index, column);
+            }
+        }
+    }
+
+    // Phase 2: Optimize the actual objective without reintroducing artificial variables.
+    let phase_2_costs = objective
+        .iter()
+        .cloned()
+        .chain(repeat_n(Rational::ZERO, columns - variables))
+        .collect::<Vec<_>>();
+    tableau.optimize(&phase_2_costs, artificials_start)?;
+
+    let mut solution = vec![Rational::ZERO; variables];
+    for (&basis, rhs) in tableau.basis.iter().zip(&tableau.rhs) {
+        if basis < variables {
+            solution[basis] = rhs.clone();
+        }
+    }
+    Ok(solution)
+}
+
+#[derive(Clone, Debug, Default)]
+struct Tableau {
+    rows: Vec<Vec<Rational>>,
+    rhs: Vec<Rational>,
+    /// The basic variable of each row.
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    /// Pivots until no column below `entering_columns` can increase the objective any further.
+    fn optimize(
+        &mut self,
+        costs: &[Rational],
+        entering_columns: usize,
+    ) -> Result<(), SimplexError> {
+        while let Some(column) =
+            (0..entering_columns).find(|&column| self.reduced_cost(costs, column) > 0)
+        {
+            let row_index = self
+                .rows
+                .iter()
+                .zip(&self.rhs)
+                .enumerate()
+                .filter(|(_, (row, _))| row[column] > 0)
+                .map(|(row_index, (row, rhs))| {
+                    (rhs / &row[column], self.basis[row_index], row_index)
+                })
+                .min()
+                .map(|(_, _, row_index)| row_index)
+                .ok_or(SimplexError::Unbounded)?;
+            self.pivot(row_index, column);
+        }
+        Ok(())
+    }
+
+    fn reduced_cost(&self, costs: &[Rational], column: usize) -> Rational {
+        self.rows
+            .iter()
+            .zip(&self.basis)
+            .fold(costs[column].clone(), |acc, (row, &basis)| {
+                acc - &costs[basis] * &row[column]
+            })
+    }
+
+    fn pivot(&mut self, pivot_row_index: usize, column: usize) {
+        let factor = self.rows[pivot_row_index][column].clone();
+        for value in &mut self.rows[pivot_row_index] {
+            *value /= &factor;
+        }
+        self.rhs[pivot_row_index] /= &factor;
+
+        let pivot_row = self.rows[pivot_row_index].clone();
+        let pivot_rhs = self.rhs[pivot_row_index].clone();
+        for (row_index, (row, rhs)) in self.rows.iter_mut().zip(&mut self.rhs).enumerate() {
+            if row_index == pivot_row_index || row[column] == 0 {
+                continue;
+            }
+            let factor = row[column].clone();
+            for (value, pivot_value) in row.iter_mut().zip(&pivot_row) {
+                *value -= &factor * pivot_value;
+            }
+            *rhs -= factor * &pivot_rhs;
+        }
+
+        self.basis[pivot_row_index] = column;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rationals(values: &[i64]) -> Vec<Rational> {
+        values.iter().copied().map(Rational::from).collect()
+    }
+
+    fn constraint(coefficients: &[i64], relation: Relation, rhs: i64) -> Constraint {
+        Constraint {
+            coefficients: rationals(coefficients),
+            relation,
+            rhs: Rational::from(rhs),
+        }
+    }
+
+    #[test]
+    fn finds_known_optimum() {
+        let constraints = [
+            constraint(&[1, 0], Relation::LessEqual, 4),
+            constraint(&[0, 2], Relation::LessEqual, 12),
+            constraint(&[3, 2], Relation::LessEqual, 18),
+        ];
+
+        assert_eq!(
+            maximize(&rationals(&[3, 5]), &constraints),
+            Ok(rationals(&[2, 6]))
+        );
+    }
+
+    #[test]
+    fn detects_infeasible_constraints() {
+        let constraints = [
+            constraint(&[1], Relation::LessEqual, 1),
+            constraint(&[1], Relation::GreaterEqual, 2),
+        ];
+
+        assert_eq!(
+            maximize(&rationals(&[1]), &constraints),
+            Err(SimplexError::Infeasible)
+        );
+    }
+
+    #[test]
+    fn detects_unbounded_objective() {
+        let constraints = [constraint(&[1, -1], Relation::LessEqual, 1)];
+
+        assert_eq!(
+            maximize(&rationals(&[1, 0]), &constraints),
+            Err(SimplexError::Unbounded)
+        );
+    }
+
+    #[test]
+    fn handles_redundant_equality_rows() {
+        // The second row is a multiple of the first, so one artificial variable stays basic at
+        // zero after phase 1.
+        let constraints = [
+            constraint(&[1, 1], Relation::Equal, 2),
+            constraint(&[2, 2], Relation::Equal, 4),
+            constraint(&[1, -1], Relation::LessEqual, 0),
+        ];
+
+        assert_eq!(
+            maximize(&rationals(&[1, 0]), &constraints),
+            Ok(rationals(&[1, 1]))
+        );
+    }
+
+    #[test]
+    fn handles_degenerate_vertices() {
+        // All constraints meet at the origin, which forces pivots that do not move.
+        let constraints = [
+            constraint(&[1, -1], Relation::LessEqual, 0),
+            constraint(&[-1, 1], Relation::LessEqual, 0),
+            constraint(&[1, 1], Relation::LessEqual, 4),
+        ];
+
+        assert_eq!(
+            maximize(&rationals(&[1, 1]), &constraints),
+            Ok(rationals(&[2, 2]))
+        );
+    }
+
+    #[test]
+    fn flips_negative_rhs() {
+        let constraints = [
+            constraint(&[-1, -1], Relation::LessEqual, -2),
+            constraint(&[0, -1], Relation::Equal, -1),
+        ];
+
+        // Minimizes x + 2y.
+        assert_eq!(
+            maximize(&rationals(&[-1, -2]), &constraints),
+            Ok(rationals(&[1, 1]))
+        );
+    }
+}
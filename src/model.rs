@@ -1,3 +1,8 @@
+pub mod fuel;
 pub mod machine;
+pub mod machine_counts;
 pub mod processing_chain;
 pub mod recipe;
+mod serde_util;
+#[cfg(test)]
+mod test_util;
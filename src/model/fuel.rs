@@ -0,0 +1,148 @@
+use std::{collections::BTreeMap, num::NonZeroU64};
+
+use malachite::{Natural, Rational};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::recipe::{Machine, Product, ProductCount, ProductForm, Recipe};
+
+/// Lists how much energy [`Generator`]s can extract from which fuels.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FuelTable {
+    /// All known generators by the name of their [`Machine`].
+    #[serde(default)]
+    pub generators: BTreeMap<Machine, Generator>,
+    /// All fuels, grouped by the [`FuelKind`] of generators that can burn them.
+    #[serde(default)]
+    pub fuels: BTreeMap<FuelKind, Fuels>,
+}
+
+impl FuelTable {
+    pub fn generator(&self, machine: &Machine) -> Option<&Generator> {
+        self.generators.get(machine)
+    }
+
+    /// All fuels that can be burned by the given `machine` alongside their EU per unit.
+    ///
+    /// Empty if the `machine` is not a known [`Generator`].
+    pub fn fuels(&self, machine: &Machine) -> impl Iterator<Item = (Product, NonZeroU64)> + '_ {
+        self.generator(machine)
+            .and_then(|generator| self.fuels.get(&generator.fuel_kind))
+            .into_iter()
+            .flat_map(|fuels| {
+                fuels.eu_per_unit.iter().map(|(name, &eu)| {
+                    let product = Product {
+                        name: name.clone(),
+                        form: fuels.product_form,
+                    };
+                    (product, eu)
+                })
+            })
+    }
+
+    /// Creates the [`Recipe`] of a `machine` burning the given `fuel`.
+    ///
+    /// The recipe consumes the smallest amount of fuel that results in a whole number of ticks.
+    pub fn recipe(&self, machine: &Machine, fuel: &Product) -> Result<Recipe, FuelError> {
+        let generator = self
+            .generator(machine)
+            .ok_or_else(|| FuelError::UnknownGenerator(machine.clone()))?;
+        let (_, eu_per_unit) = self
+            .fuels(machine)
+            .find(|(product, _)| product == fuel)
+            .ok_or_else(|| FuelError::UnknownFuel {
+                machine: machine.clone(),
+                fuel: fuel.clone(),
+            })?;
+
+        let ticks_per_unit = Rational::from(eu_per_unit.get())
+            * Rational::from_unsigneds(generator.efficiency.get(), 100)
+            / Rational::from(generator.eu_per_tick.get());
+        let (ticks, units) = ticks_per_unit.into_numerator_and_denominator();
+
+        let mut recipe = Recipe::new(machine.clone());
+        recipe.ticks = non_zero_u64(ticks)?;
+        recipe.eu_per_tick =
+            i64::try_from(generator.eu_per_tick.get()).map_err(|_| FuelError::OutOfRange)?;
+        recipe.consumed.push(ProductCount {
+            count: non_zero_u64(units)?,
+            ..ProductCount::new(fuel.clone())
+        });
+        Ok(recipe)
+    }
+}
+
+/// A [`Machine`] that turns fuel into EU.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Generator {
+    /// Which fuels this generator can burn.
+    pub fuel_kind: FuelKind,
+    /// The EU/t that is produced while burning fuel.
+    pub eu_per_tick: NonZeroU64,
+    /// The percentage of the fuel's EU that is actually produced.
+    pub efficiency: NonZeroU64,
+}
+
+/// The fuels of a single [`FuelKind`].
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fuels {
+    /// The form of all fuels in this group, usually [`ProductForm::Fluid`].
+    #[serde(default)]
+    pub product_form: ProductForm,
+    /// The EU per unit of each fuel by name.
+    pub eu_per_unit: BTreeMap<String, NonZeroU64>,
+}
+
+/// Groups fuels by the type of [`Generator`] that can burn them, e.g. `gas` or `combustion`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FuelKind(pub String);
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Error)]
+pub enum FuelError {
+    #[error("{} is not a known generator", .0.name)]
+    UnknownGenerator(Machine),
+    #[error("{} cannot burn {}", machine.name, fuel.name)]
+    UnknownFuel { machine: Machine, fuel: Product },
+    #[error("the fuel value is out of range")]
+    OutOfRange,
+}
+
+fn non_zero_u64(value: Natural) -> Result<NonZeroU64, FuelError> {
+    u64::try_from(&value)
+        .ok()
+        .and_then(NonZeroU64::new)
+        .ok_or(FuelError::OutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipe_burns_the_smallest_whole_number_of_ticks() {
+        let fuel_table: FuelTable = serde_json::from_str(include_str!("../../fuels.json")).unwrap();
+        let machine = Machine {
+            name: "Basic Gas Turbine".into(),
+        };
+        let benzene = Product {
+            name: "Benzene".into(),
+            form: ProductForm::Fluid,
+        };
+
+        // 360 EU/L at 95% efficiency and 32 EU/t last 171/16 ticks per liter
+        let recipe = fuel_table.recipe(&machine, &benzene).unwrap();
+        assert_eq!(recipe.ticks.get(), 171);
+        assert_eq!(recipe.eu_per_tick, 32);
+        assert_eq!(
+            recipe.consumed,
+            [ProductCount {
+                count: NonZeroU64::new(16).unwrap(),
+                ..ProductCount::new(benzene)
+            }]
+        );
+    }
+}
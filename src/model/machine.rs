@@ -9,7 +9,12 @@ use std::{
 use enum_map::Enum;
 use enumset::EnumSetType;
 use malachite::{
-    num::basic::traits::{One, Zero},
+    num::{
+        arithmetic::traits::{Abs, Pow},
+        basic::traits::{One, Zero},
+        conversion::traits::RoundingFrom,
+    },
+    rounding_modes::RoundingMode,
     Integer, Rational,
 };
 use serde::{
@@ -18,42 +23,152 @@ use serde::{
 };
 use thiserror::Error;
 
+use super::{
+    recipe::Recipe,
+    serde_util::{is_one, one},
+};
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Machines {
     /// A fixed number of machines that don't require power and run at regular speed.
     Eco(u64),
+    /// A collection of machines that are powered by steam instead of EU.
+    Steam(SteamMachines),
     /// A collection of machines at certain [`Voltage`] levels.
     Power(ClockedMachines),
 }
 
 impl Machines {
-    /// Returns how fast a recipe is produced for its given `recipe_voltage`.
-    pub fn speed_factor(
-        &self,
-        recipe_voltage: Option<Voltage>,
-    ) -> Result<Rational, MachinePowerError> {
-        match (recipe_voltage, self) {
+    /// Returns how fast the given `recipe` is produced.
+    pub fn speed_factor(&self, recipe: &Recipe) -> Result<Rational, MachinePowerError> {
+        match (recipe.voltage(), self) {
             (None, Self::Eco(count)) => Ok(Rational::from(*count)),
+            (Some(recipe_voltage), Self::Steam(steam_machines)) => {
+                steam_machines.speed_factor(recipe_voltage, recipe)
+            }
+            (Some(recipe_voltage), Self::Power(clocked_machines)) => {
+                clocked_machines.speed_factor(recipe_voltage, recipe)
+            }
+            (None, Self::Steam(_) | Self::Power(_)) => Err(MachinePowerError::RequiresEco),
+            (Some(_), Self::Eco(_)) => Err(MachinePowerError::RequiresPower),
+        }
+    }
+
+    /// Returns how fast each group of machines is processing the given `recipe`, alongside the
+    /// number of overclocking steps that were actually applied.
+    ///
+    /// The tiers are used to boost chanced outputs; see [`Recipe::chance_boost`].
+    pub fn speed_factors_per_tier(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(u8, Rational)>, MachinePowerError> {
+        match (recipe.voltage(), self) {
+            (None, Self::Eco(count)) => Ok(vec![(0, Rational::from(*count))]),
+            (Some(recipe_voltage), Self::Steam(steam_machines)) => Ok(vec![(
+                0,
+                steam_machines.speed_factor(recipe_voltage, recipe)?,
+            )]),
             (Some(recipe_voltage), Self::Power(clocked_machines)) => {
-                Ok(clocked_machines.speed_factor(recipe_voltage))
+                clocked_machines.speed_factors_per_tier(recipe_voltage, recipe)
+            }
+            (None, Self::Steam(_) | Self::Power(_)) => Err(MachinePowerError::RequiresEco),
+            (Some(_), Self::Eco(_)) => Err(MachinePowerError::RequiresPower),
+        }
+    }
+
+    /// Returns how fast a single machine of each [`MachineGroup`] processes the given `recipe`.
+    pub fn speed_factors_per_machine(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(MachineGroup, Rational)>, MachinePowerError> {
+        match (recipe.voltage(), self) {
+            (None, Self::Eco(_)) => Ok(vec![(MachineGroup::Eco, Rational::ONE)]),
+            (Some(recipe_voltage), Self::Steam(steam_machines)) => {
+                SteamMachines::check_recipe(recipe_voltage, recipe)?;
+                Ok(steam_machines
+                    .steam
+                    .keys()
+                    .map(|&tier| (MachineGroup::Steam(tier), tier.speed_factor()))
+                    .collect())
             }
-            (None, Self::Power(_)) => Err(MachinePowerError::RequiresEco),
+            (Some(recipe_voltage), Self::Power(clocked_machines)) => clocked_machines
+                .machines
+                .keys()
+                .map(|clocked_machine| {
+                    Ok((
+                        MachineGroup::Power(*clocked_machine),
+                        clocked_machines.machine_speed_factor(
+                            clocked_machine,
+                            recipe_voltage,
+                            recipe,
+                        )?,
+                    ))
+                })
+                .collect(),
+            (None, Self::Steam(_) | Self::Power(_)) => Err(MachinePowerError::RequiresEco),
             (Some(_), Self::Eco(_)) => Err(MachinePowerError::RequiresPower),
         }
     }
 
-    pub fn eu_per_tick(&self, recipe_eu_per_tick: i64) -> Result<Integer, MachinePowerError> {
-        match (recipe_eu_per_tick.try_into().ok(), self) {
+    /// Returns the EU/t of all machines processing the given `recipe`.
+    ///
+    /// [`Machines::Steam`] never deal with EU; see [`Self::steam_per_sec`] instead.
+    pub fn eu_per_tick(&self, recipe: &Recipe) -> Result<Integer, MachinePowerError> {
+        match (recipe.voltage(), self) {
             (None, Self::Eco(_)) => Ok(Integer::ZERO),
-            (Some(recipe_eu_per_tick), Self::Power(clocked_machines)) => {
-                Ok(clocked_machines.eu_per_tick(recipe_eu_per_tick))
+            (Some(recipe_voltage), Self::Steam(steam_machines)) => steam_machines
+                .speed_factor(recipe_voltage, recipe)
+                .map(|_| Integer::ZERO),
+            (Some(recipe_voltage), Self::Power(clocked_machines)) => {
+                clocked_machines.eu_per_tick(recipe_voltage, recipe)
             }
-            (None, Self::Power(_)) => Err(MachinePowerError::RequiresEco),
+            (None, Self::Steam(_) | Self::Power(_)) => Err(MachinePowerError::RequiresEco),
             (Some(_), Self::Eco(_)) => Err(MachinePowerError::RequiresPower),
         }
     }
 
+    /// Returns the steam in liters per second consumed by all machines processing the given
+    /// `recipe`.
+    ///
+    /// Only [`Machines::Steam`] consume any steam.
+    pub fn steam_per_sec(&self, recipe: &Recipe) -> Result<Rational, MachinePowerError> {
+        match self {
+            Self::Steam(steam_machines) => {
+                let recipe_voltage = recipe.voltage().ok_or(MachinePowerError::RequiresEco)?;
+                steam_machines.steam_per_sec(recipe_voltage, recipe)
+            }
+            Self::Eco(_) | Self::Power(_) => Ok(Rational::ZERO),
+        }
+    }
+
+    /// The total number of machines, regardless of their tier.
+    pub fn count(&self) -> u64 {
+        match self {
+            Self::Eco(count) => *count,
+            Self::Steam(steam_machines) => {
+                steam_machines.steam.values().map(|count| count.get()).sum()
+            }
+            Self::Power(clocked_machines) => clocked_machines
+                .machines
+                .values()
+                .map(|count| count.get())
+                .sum(),
+        }
+    }
+
+    pub fn into_steam(&mut self) -> &mut SteamMachines {
+        match self {
+            Machines::Steam(_) => {}
+            _ => *self = Self::Steam(Default::default()),
+        }
+
+        match self {
+            Machines::Steam(steam_machines) => steam_machines,
+            _ => unreachable!(),
+        }
+    }
+
     pub fn into_clocked(&mut self) -> &mut ClockedMachines {
         match self {
             Machines::Power(_) => {}
@@ -85,46 +200,478 @@ impl Default for Machines {
     }
 }
 
+/// Identical machines within [`Machines`], which all process a recipe at the same speed.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MachineGroup {
+    Eco,
+    Steam(SteamTier),
+    Power(ClockedMachine),
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Error)]
 pub enum MachinePowerError {
     #[error("recipe requires machines that do not deal with power")]
     RequiresEco,
     #[error("recipe requires machines that deal with power")]
     RequiresPower,
+    #[error("steam machines cannot run recipes that produce power")]
+    SteamGenerator,
+    #[error("recipe requires machines with coils")]
+    RequiresCoils,
+    #[error("recipe requires {required}K but coils only reach {available}K")]
+    InsufficientHeat { required: u64, available: u64 },
+    #[error("recipe requires {required} EU/t but machines can only draw {available} EU/t")]
+    InsufficientPower { required: u64, available: u64 },
+    #[error("recipe requires {required}A but machines can only draw {available}A")]
+    InsufficientAmperage { required: u64, available: u64 },
+    #[error("recipe requires {required} but machines only run at {available}")]
+    InsufficientTier {
+        required: Voltage,
+        available: Voltage,
+    },
 }
 
+/// Machines that are powered by steam, e.g. a "**Bronze** Macerator".
+///
+/// Serialized within a `steam` field to tell it apart from [`ClockedMachines`].
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SteamMachines {
+    pub steam: BTreeMap<SteamTier, NonZeroU64>,
+}
+
+impl SteamMachines {
+    /// Steam machines can only process recipes up to this [`Voltage`].
+    pub const MAX_VOLTAGE: Voltage = Voltage::Low;
+    /// Steam machines can only process recipes that draw up to this many amps.
+    pub const MAX_AMPERAGE: NonZeroU64 = NonZeroU64::MIN;
+
+    pub fn speed_factor(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Rational, MachinePowerError> {
+        Self::check_recipe(recipe_voltage, recipe)?;
+        Ok(self
+            .steam
+            .iter()
+            .map(|(tier, count)| tier.speed_factor() * Rational::from(count.get()))
+            .sum())
+    }
+
+    /// The steam in liters per second consumed by all machines.
+    pub fn steam_per_sec(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Rational, MachinePowerError> {
+        Self::check_recipe(recipe_voltage, recipe)?;
+        Ok(self
+            .steam
+            .iter()
+            .map(|(tier, count)| {
+                Rational::from(tier.steam_per_tick(recipe.eu_per_tick.unsigned_abs()) * 20)
+                    * Rational::from(count.get())
+            })
+            .sum())
+    }
+
+    fn check_recipe(recipe_voltage: Voltage, recipe: &Recipe) -> Result<(), MachinePowerError> {
+        if recipe.eu_per_tick > 0 {
+            Err(MachinePowerError::SteamGenerator)
+        } else if recipe.heat.is_some() {
+            // steam machines have no coils
+            Err(MachinePowerError::RequiresCoils)
+        } else if recipe_voltage > Self::MAX_VOLTAGE {
+            Err(MachinePowerError::InsufficientTier {
+                required: recipe_voltage,
+                available: Self::MAX_VOLTAGE,
+            })
+        } else if recipe.amperage > Self::MAX_AMPERAGE {
+            Err(MachinePowerError::InsufficientAmperage {
+                required: recipe.amperage.get(),
+                available: Self::MAX_AMPERAGE.get(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The tier of a [`SteamMachines`] entry.
+#[derive(
+    Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Enum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SteamTier {
+    /// Runs recipes at half speed.
+    Bronze,
+    /// High pressure machines that run recipes at full speed, using twice the steam per tick.
+    Steel,
+}
+
+impl SteamTier {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Bronze => "Bronze",
+            Self::Steel => "Steel",
+        }
+    }
+
+    /// How fast a recipe is processed compared to its regular duration.
+    pub fn speed_factor(self) -> Rational {
+        match self {
+            Self::Bronze => Rational::from_unsigneds(1u8, 2),
+            Self::Steel => Rational::ONE,
+        }
+    }
+
+    /// The steam in liters per tick consumed for a recipe with the given `eu_per_tick`.
+    ///
+    /// Steel machines use twice the steam of bronze machines at twice the speed, so both tiers
+    /// use the same amount of steam per recipe.
+    pub const fn steam_per_tick(self, recipe_eu_per_tick: u64) -> u64 {
+        match self {
+            Self::Bronze => 2 * recipe_eu_per_tick,
+            Self::Steel => 4 * recipe_eu_per_tick,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ClockedMachines {
+    /// The [`Coil`]s of all machines, which is only relevant for recipes with a
+    /// [`Recipe::heat`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coil: Option<Coil>,
+    /// How many amps each machine can draw at its [`ClockedMachine::tier`].
+    ///
+    /// E.g. `2` for a multiblock with two energy hatches.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub amperage: NonZeroU64,
     #[serde(flatten)]
     pub machines: BTreeMap<ClockedMachine, NonZeroU64>,
 }
 
+impl Default for ClockedMachines {
+    fn default() -> Self {
+        Self {
+            coil: None,
+            amperage: NonZeroU64::MIN,
+            machines: Default::default(),
+        }
+    }
+}
+
 impl ClockedMachines {
-    pub fn speed_factor(&self, recipe_voltage: Voltage) -> Rational {
+    pub fn speed_factor(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Rational, MachinePowerError> {
         self.machines
             .iter()
             .map(|(clocked_machine, count)| {
-                clocked_machine.underclocking.speed_factor(recipe_voltage)
-                    * Rational::from(count.get())
+                Ok(
+                    self.machine_speed_factor(clocked_machine, recipe_voltage, recipe)?
+                        * Rational::from(count.get()),
+                )
             })
             .sum()
     }
 
-    pub fn eu_per_tick(&self, recipe_eu_per_tick: NonZeroI64) -> Integer {
-        let recipe_voltage = Voltage::from_signed_eu_per_tick(recipe_eu_per_tick);
+    /// See [`Machines::speed_factors_per_tier`].
+    pub fn speed_factors_per_tier(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Vec<(u8, Rational)>, MachinePowerError> {
         self.machines
             .iter()
             .map(|(clocked_machine, count)| {
-                let eu = Integer::from(recipe_eu_per_tick.get())
-                    << clocked_machine.underclocking.eu_factor_log2(recipe_voltage);
-                assert!(
-                    eu != 0,
-                    "underclocking should not be able to result in less than 1 eu per tick"
+                let heat_bonus = clocked_machine.heat_bonus(recipe, self.coil)?;
+                let parallels =
+                    self.effective_parallels(clocked_machine, recipe_voltage, recipe)?;
+                let overclock = clocked_machine.overclock(
+                    recipe_voltage,
+                    recipe,
+                    heat_bonus,
+                    parallels,
+                    self.amperage,
                 );
-                eu * Integer::from(count.get())
+                Ok((
+                    overclock.steps.max(0).unsigned_abs(),
+                    overclock.speed_factor(recipe.ticks)
+                        * Rational::from(parallels.get())
+                        * Rational::from(count.get()),
+                ))
+            })
+            .collect()
+    }
+
+    pub fn eu_per_tick(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Integer, MachinePowerError> {
+        self.machines
+            .iter()
+            .map(|(clocked_machine, count)| {
+                Ok(
+                    self.machine_eu_per_tick(clocked_machine, recipe_voltage, recipe)?
+                        * Integer::from(count.get()),
+                )
             })
             .sum()
     }
+
+    /// The EU/t that a single machine of the given `tier` can draw.
+    pub fn max_eu_per_tick(&self, tier: Voltage) -> Integer {
+        Integer::from(tier.max_eu_per_tick().get()) * Integer::from(self.amperage.get())
+    }
+
+    /// How many of [`ClockedMachine::parallels`] can actually run at once for the given
+    /// `recipe`.
+    ///
+    /// The regular EU/t of all parallels combined must not exceed [`Self::max_eu_per_tick`] at
+    /// [`ClockedMachine::underclocking`], before any overclocking. If not even a single recipe
+    /// fits, [`MachinePowerError::InsufficientPower`] is returned, unless the machine is
+    /// underclocked below the `recipe_voltage` to begin with, which is reported as
+    /// [`MachinePowerError::InsufficientTier`] instead.
+    pub fn effective_parallels(
+        &self,
+        clocked_machine: &ClockedMachine,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<NonZeroU64, MachinePowerError> {
+        if clocked_machine.underclocking < recipe_voltage {
+            return Err(MachinePowerError::InsufficientTier {
+                required: recipe_voltage,
+                available: clocked_machine.underclocking,
+            });
+        }
+        let heat_bonus = clocked_machine.heat_bonus(recipe, self.coil)?;
+        let eu_per_parallel = heat_bonus.discount(recipe.eu_per_tick).abs();
+        if eu_per_parallel == 0 {
+            return Ok(clocked_machine.parallels);
+        }
+        let max_eu_per_tick = self.max_eu_per_tick(clocked_machine.underclocking);
+        let max_parallels = &max_eu_per_tick / &eu_per_parallel;
+        u64::try_from(&max_parallels)
+            .ok()
+            .and_then(NonZeroU64::new)
+            .map(|max_parallels| clocked_machine.parallels.min(max_parallels))
+            .ok_or_else(|| MachinePowerError::InsufficientPower {
+                required: saturating_u64(eu_per_parallel),
+                available: saturating_u64(max_eu_per_tick),
+            })
+    }
+
+    /// How fast a single machine processes the given `recipe`, including its parallels.
+    fn machine_speed_factor(
+        &self,
+        clocked_machine: &ClockedMachine,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Rational, MachinePowerError> {
+        let heat_bonus = clocked_machine.heat_bonus(recipe, self.coil)?;
+        let parallels = self.effective_parallels(clocked_machine, recipe_voltage, recipe)?;
+        Ok(clocked_machine
+            .overclock(recipe_voltage, recipe, heat_bonus, parallels, self.amperage)
+            .speed_factor(recipe.ticks)
+            * Rational::from(parallels.get()))
+    }
+
+    /// The EU/t of a single machine processing the given `recipe`, including its parallels.
+    fn machine_eu_per_tick(
+        &self,
+        clocked_machine: &ClockedMachine,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Integer, MachinePowerError> {
+        let heat_bonus = clocked_machine.heat_bonus(recipe, self.coil)?;
+        let parallels = self.effective_parallels(clocked_machine, recipe_voltage, recipe)?;
+        let eu_per_parallel = heat_bonus.discount(recipe.eu_per_tick)
+            << clocked_machine
+                .overclock(recipe_voltage, recipe, heat_bonus, parallels, self.amperage)
+                .eu_factor_log2(recipe.overclocking);
+        assert!(
+            eu_per_parallel != 0,
+            "overclocking should not result in less than 1 eu per tick"
+        );
+        Ok(eu_per_parallel * Integer::from(parallels.get()))
+    }
+}
+
+fn saturating_u64(value: Integer) -> u64 {
+    u64::try_from(&value).unwrap_or(u64::MAX)
+}
+
+/// The heating coils of an Electric Blast Furnace or similar machines.
+#[derive(
+    Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Enum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Coil {
+    Cupronickel,
+    Kanthal,
+    Nichrome,
+    Tpv,
+    HssG,
+    HssS,
+    Naquadah,
+    NaquadahAlloy,
+    Trinium,
+    ElectrumFlux,
+    AwakenedDraconium,
+    Infinity,
+    Hypogen,
+    Eternal,
+}
+
+impl Coil {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Cupronickel => "Cupronickel",
+            Self::Kanthal => "Kanthal",
+            Self::Nichrome => "Nichrome",
+            Self::Tpv => "TPV-Alloy",
+            Self::HssG => "HSS-G",
+            Self::HssS => "HSS-S",
+            Self::Naquadah => "Naquadah",
+            Self::NaquadahAlloy => "Naquadah Alloy",
+            Self::Trinium => "Trinium",
+            Self::ElectrumFlux => "Electrum Flux",
+            Self::AwakenedDraconium => "Awakened Draconium",
+            Self::Infinity => "Infinity",
+            Self::Hypogen => "Hypogen",
+            Self::Eternal => "Eternal",
+        }
+    }
+
+    /// The heat in Kelvin that these coils provide on their own.
+    ///
+    /// E.g. [`Coil::Cupronickel`] provides `1801K` and each following tier `900K` more.
+    pub const fn heat(self) -> u64 {
+        1801 + 900 * self as u64
+    }
+
+    /// The heat in Kelvin that these coils provide in a machine of the given `tier`.
+    ///
+    /// Every [`Voltage`] tier above [`Voltage::Medium`] adds another `100K`.
+    pub const fn heat_at(self, tier: Voltage) -> u64 {
+        self.heat() + 100 * (tier as u64).saturating_sub(Voltage::Medium as u64)
+    }
+}
+
+/// The bonuses of a machine with [`Coil`]s whose heat exceeds the [`Recipe::heat`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HeatBonus {
+    /// Reduces the EU/t by 5% for every `900K` of excess heat.
+    pub eu_discounts: u64,
+    /// Overclocks that are perfect regardless of [`Recipe::overclocking`], one for every `1800K`
+    /// of excess heat.
+    pub perfect_overclocks: u64,
+}
+
+impl HeatBonus {
+    /// Calculates the bonus of a machine of the given `tier`.
+    ///
+    /// Recipes without [`Recipe::heat`] never get a bonus.
+    pub fn new(
+        coil: Option<Coil>,
+        tier: Voltage,
+        recipe: &Recipe,
+    ) -> Result<Self, MachinePowerError> {
+        let Some(required) = recipe.heat else {
+            return Ok(Self::default());
+        };
+        let required = required.get();
+        let available = coil.ok_or(MachinePowerError::RequiresCoils)?.heat_at(tier);
+        let excess_heat =
+            available
+                .checked_sub(required)
+                .ok_or(MachinePowerError::InsufficientHeat {
+                    required,
+                    available,
+                })?;
+
+        Ok(Self {
+            eu_discounts: excess_heat / 900,
+            perfect_overclocks: excess_heat / 1800,
+        })
+    }
+
+    /// Applies [`Self::eu_discounts`] to the `recipe_eu_per_tick` rounding towards zero.
+    ///
+    /// Never goes below a single EU/t.
+    pub fn discount(&self, recipe_eu_per_tick: i64) -> Integer {
+        let factor = Rational::from_unsigneds(19u64, 20).pow(self.eu_discounts);
+        let discounted = Integer::rounding_from(
+            Rational::from(recipe_eu_per_tick) * factor,
+            RoundingMode::Down,
+        )
+        .0;
+        if discounted == 0 {
+            Integer::from(recipe_eu_per_tick.signum())
+        } else {
+            discounted
+        }
+    }
+}
+
+/// How a [`Recipe`] is sped up when it is processed by a machine above its [`Voltage`].
+#[derive(
+    Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Overclocking {
+    /// Each overclocking step doubles the speed and quadruples the power consumption.
+    #[default]
+    Normal,
+    /// Each overclocking step quadruples both the speed and the power consumption.
+    Perfect,
+    /// The recipe is always processed at its regular speed and power consumption.
+    None,
+}
+
+impl Overclocking {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal Overclocking",
+            Self::Perfect => "Perfect Overclocking",
+            Self::None => "No Overclocking",
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Normal => "2× speed for 4× power per overclocking step.",
+            Self::Perfect => "4× speed for 4× power per overclocking step.",
+            Self::None => "Regular speed and power regardless of the machine tier.",
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+
+    /// How much faster a machine runs per overclocking step in `log2`.
+    pub const fn speed_factor_log2_per_step(self) -> i8 {
+        match self {
+            Self::Normal => 1,
+            Self::Perfect => 2,
+            Self::None => 0,
+        }
+    }
+
+    /// How much more energy a machine consumes per overclocking step in `log2`.
+    pub const fn eu_factor_log2_per_step(self) -> i8 {
+        match self {
+            Self::Normal | Self::Perfect => 2,
+            Self::None => 0,
+        }
+    }
 }
 
 /// The tier and clocking of some machine, e.g. a "**HV** Macerator" running at **LV**.
@@ -132,14 +679,19 @@ impl ClockedMachines {
 pub struct ClockedMachine {
     /// The [`Voltage`] tier of the machine.
     ///
-    /// Not used by calculations, since only the [`Self::clocking`] is relevant for processing
-    /// speed and power consumption.
+    /// Only limits the EU/t that the machine can draw, since otherwise only the
+    /// [`Self::underclocking`] is relevant for processing speed and power consumption.
     tier: Voltage,
     /// The [`Voltage`] that the machine is underclocked to/running at.
     ///
     /// Must not be greater than [`Self::tier`] since machines cannot be overclocked. Only
     /// recipes can be overclocked by using a higher [`Self::tier`] of machine.
     underclocking: Voltage,
+    /// The maximum number of recipes that a multiblock can process at once.
+    ///
+    /// How many of them actually run is limited by the EU/t available at [`Self::underclocking`];
+    /// see [`ClockedMachines::effective_parallels`].
+    parallels: NonZeroU64,
 }
 
 impl PartialOrd for ClockedMachine {
@@ -150,7 +702,11 @@ impl PartialOrd for ClockedMachine {
 
 impl Ord for ClockedMachine {
     fn cmp(&self, other: &Self) -> Ordering {
-        (self.tier, other.underclocking).cmp(&(other.tier, self.underclocking))
+        (self.tier, other.underclocking, self.parallels).cmp(&(
+            other.tier,
+            self.underclocking,
+            other.parallels,
+        ))
     }
 }
 
@@ -159,6 +715,7 @@ impl ClockedMachine {
         Self {
             tier,
             underclocking: tier,
+            parallels: NonZeroU64::MIN,
         }
     }
 
@@ -167,9 +724,14 @@ impl ClockedMachine {
         Self {
             tier,
             underclocking,
+            parallels: NonZeroU64::MIN,
         }
     }
 
+    pub fn with_parallels(self, parallels: NonZeroU64) -> Self {
+        Self { parallels, ..self }
+    }
+
     pub fn tier(&self) -> Voltage {
         self.tier
     }
@@ -177,6 +739,42 @@ impl ClockedMachine {
     pub fn underclocking(&self) -> Voltage {
         self.underclocking
     }
+
+    pub fn parallels(&self) -> NonZeroU64 {
+        self.parallels
+    }
+
+    /// Overclocks the given `recipe` to [`Self::underclocking`] while running `parallels`
+    /// recipes at once, drawing up to `amperage` amps.
+    ///
+    /// The `parallels` should come from [`ClockedMachines::effective_parallels`], which also
+    /// rejects machines that are underclocked below the `recipe_voltage`.
+    pub fn overclock(
+        &self,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+        heat_bonus: HeatBonus,
+        parallels: NonZeroU64,
+        amperage: NonZeroU64,
+    ) -> Overclock {
+        Overclock::new(
+            self.underclocking,
+            amperage,
+            recipe_voltage,
+            recipe,
+            heat_bonus,
+            parallels,
+        )
+    }
+
+    /// See [`HeatBonus::new`].
+    pub fn heat_bonus(
+        &self,
+        recipe: &Recipe,
+        coil: Option<Coil>,
+    ) -> Result<HeatBonus, MachinePowerError> {
+        HeatBonus::new(coil, self.tier, recipe)
+    }
 }
 
 impl Serialize for ClockedMachine {
@@ -184,10 +782,17 @@ impl Serialize for ClockedMachine {
     where
         S: Serializer,
     {
-        if self.tier == self.underclocking {
-            serializer.collect_str(&format_args!("{}", self.tier))
-        } else {
-            serializer.collect_str(&format_args!("{}@{}", self.tier, self.underclocking))
+        let parallels = self.parallels.get();
+        match (self.tier == self.underclocking, parallels == 1) {
+            (true, true) => serializer.collect_str(&format_args!("{}", self.tier)),
+            (false, true) => {
+                serializer.collect_str(&format_args!("{}@{}", self.tier, self.underclocking))
+            }
+            (true, false) => serializer.collect_str(&format_args!("{}*{parallels}", self.tier)),
+            (false, false) => serializer.collect_str(&format_args!(
+                "{}@{}*{parallels}",
+                self.tier, self.underclocking
+            )),
         }
     }
 }
@@ -196,6 +801,12 @@ impl<'de> Deserialize<'de> for ClockedMachine {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let clocked_machine: &str = Deserialize::deserialize(deserializer)?;
 
+        let (clocked_machine, parallels_str) = clocked_machine
+            .split_once('*')
+            .map_or((clocked_machine, None), |(clocked_machine, parallels)| {
+                (clocked_machine, Some(parallels))
+            });
+
         let (tier_str, clocking_str) = clocked_machine
             .split_once('@')
             .unwrap_or((clocked_machine, clocked_machine));
@@ -209,10 +820,17 @@ impl<'de> Deserialize<'de> for ClockedMachine {
         let tier = parse_voltage(tier_str)?;
         let clocking = parse_voltage(clocking_str)?;
 
+        let parallels = parallels_str.map_or(Ok(NonZeroU64::MIN), |parallels| {
+            parallels.parse().map_err(|_| {
+                D::Error::invalid_value(Unexpected::Str(parallels), &"non-zero parallel count")
+            })
+        })?;
+
         if clocking <= tier {
             Ok(Self {
                 tier,
                 underclocking: clocking,
+                parallels,
             })
         } else {
             Err(D::Error::invalid_value(
@@ -223,6 +841,102 @@ impl<'de> Deserialize<'de> for ClockedMachine {
     }
 }
 
+/// The result of processing a [`Recipe`] by a machine running at some [`Voltage`].
+///
+/// Unlike [`Voltage::speed_factor`], this takes into account that the game rounds durations
+/// down to whole ticks and never goes below a single tick.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Overclock {
+    /// The duration of a single processing cycle.
+    pub ticks: NonZeroU64,
+    /// How many recipes are processed at once per processing cycle.
+    ///
+    /// Only exceeds `1` for recipes with [`Recipe::subtick_parallels`].
+    pub parallels: NonZeroU64,
+    /// The number of overclocking steps that were actually applied.
+    ///
+    /// Can be lower than [`Voltage::overclocking_steps`] if the duration reached a single tick.
+    pub steps: i8,
+}
+
+impl Overclock {
+    /// Expects `voltage` to be at least the `recipe_voltage`; below it, no overclocking is applied.
+    ///
+    /// Running multiple `parallels` uses up part of the EU/t of the `voltage` first, so only the
+    /// remaining EU/t is available for overclocking. The overclocked EU/t never exceeds what
+    /// the machine can draw at `voltage` with its `amperage`.
+    pub fn new(
+        voltage: Voltage,
+        amperage: NonZeroU64,
+        recipe_voltage: Voltage,
+        recipe: &Recipe,
+        heat_bonus: HeatBonus,
+        parallels: NonZeroU64,
+    ) -> Self {
+        let speed_factor_log2 = recipe.overclocking.speed_factor_log2_per_step();
+        let perfect_speed_factor_log2 = if recipe.overclocking == Overclocking::None {
+            speed_factor_log2
+        } else {
+            Overclocking::Perfect.speed_factor_log2_per_step()
+        };
+        let parallel_eu_per_tick =
+            heat_bonus.discount(recipe.eu_per_tick).abs() * Integer::from(parallels.get());
+        let parallel_voltage = NonZeroU64::new(saturating_u64(parallel_eu_per_tick.clone()))
+            .map_or(recipe_voltage, |eu_per_tick| {
+                Voltage::from_eu_per_tick(eu_per_tick, amperage).max(recipe_voltage)
+            });
+        let max_eu_per_tick =
+            Integer::from(voltage.max_eu_per_tick().get()) * Integer::from(amperage.get());
+        let eu_factor_log2 = recipe.overclocking.eu_factor_log2_per_step();
+        let mut steps = voltage.overclocking_steps(parallel_voltage).max(0);
+        while steps > 0 && (&parallel_eu_per_tick << (eu_factor_log2 * steps)) > max_eu_per_tick {
+            steps -= 1;
+        }
+
+        let mut ticks = recipe.ticks;
+        let mut parallels = NonZeroU64::MIN;
+
+        let mut applied_steps = 0;
+        for step in 0..steps {
+            let speed_factor_log2 =
+                if u64::from(step.unsigned_abs()) < heat_bonus.perfect_overclocks {
+                    perfect_speed_factor_log2
+                } else {
+                    speed_factor_log2
+                };
+            match NonZeroU64::new(ticks.get() >> speed_factor_log2) {
+                Some(overclocked_ticks) => ticks = overclocked_ticks,
+                None if recipe.subtick_parallels => {
+                    parallels = parallels.saturating_mul(pow2(speed_factor_log2));
+                }
+                None => break,
+            }
+            applied_steps += 1;
+        }
+
+        Self {
+            ticks,
+            parallels,
+            steps: applied_steps,
+        }
+    }
+
+    /// How much faster the recipe is processed compared to its regular `ticks`.
+    pub fn speed_factor(&self, recipe_ticks: NonZeroU64) -> Rational {
+        Rational::from_unsigneds(recipe_ticks.get(), self.ticks.get())
+            * Rational::from(self.parallels.get())
+    }
+
+    /// How much more energy is consumed in `log2`.
+    pub fn eu_factor_log2(&self, overclocking: Overclocking) -> i8 {
+        overclocking.eu_factor_log2_per_step() * self.steps
+    }
+}
+
+fn pow2(exponent: i8) -> NonZeroU64 {
+    NonZeroU64::new(1 << exponent).expect("exponent should be in range")
+}
+
 #[derive(Debug, Hash, PartialOrd, Ord, Enum, EnumSetType)]
 pub enum Voltage {
     UltraLow,
@@ -299,17 +1013,26 @@ impl Voltage {
         }
     }
 
-    pub fn from_eu_per_tick(eu_per_tick: NonZeroU64) -> Self {
+    /// Returns the minimum [`Voltage`] required for the given `eu_per_tick`.
+    ///
+    /// The `eu_per_tick` is split evenly across the given `amperage`, e.g. `33` EU/t at 1A
+    /// already require [`Voltage::Medium`].
+    pub fn from_eu_per_tick(eu_per_tick: NonZeroU64, amperage: NonZeroU64) -> Self {
+        let eu_per_tick_per_amp = eu_per_tick.get().div_ceil(amperage.get());
+        let eu_per_tick_per_amp_log2 = match eu_per_tick_per_amp {
+            1 => 0,
+            _ => (eu_per_tick_per_amp - 1).ilog2() + 1,
+        };
         Self::from_usize(
-            (eu_per_tick.ilog2().saturating_sub(3))
+            (eu_per_tick_per_amp_log2.saturating_sub(3))
                 .div_ceil(2)
                 .try_into()
                 .unwrap_or(usize::MAX),
         )
     }
 
-    pub fn from_signed_eu_per_tick(eu_per_tick: NonZeroI64) -> Self {
-        Self::from_eu_per_tick(eu_per_tick.unsigned_abs())
+    pub fn from_signed_eu_per_tick(eu_per_tick: NonZeroI64, amperage: NonZeroU64) -> Self {
+        Self::from_eu_per_tick(eu_per_tick.unsigned_abs(), amperage)
     }
 
     pub const fn max_eu_per_tick(self) -> NonZeroU64 {
@@ -329,17 +1052,19 @@ impl Voltage {
     /// How much faster (or slower) a machine is running for a given `recipe_voltage`.
     ///
     /// E.g. [`Voltage::High`] will run four times faster for a `recipe_voltage` of
-    /// [`Voltage::Low`].
-    pub fn speed_factor(self, recipe_voltage: Voltage) -> Rational {
-        Rational::ONE << self.overclocking_steps(recipe_voltage)
+    /// [`Voltage::Low`] with [`Overclocking::Normal`].
+    pub fn speed_factor(self, recipe_voltage: Voltage, overclocking: Overclocking) -> Rational {
+        let speed_factor_log2 =
+            overclocking.speed_factor_log2_per_step() * self.overclocking_steps(recipe_voltage);
+        Rational::ONE << speed_factor_log2
     }
 
     /// How much more energy a machine is consuming for a given `recipe_voltage` in `log2`.
     ///
     /// E.g. [`Voltage::High`] will require sixteen times more energy for a `recipe_voltage` of
-    /// [`Voltage::Low`].
-    pub fn eu_factor_log2(self, recipe_voltage: Voltage) -> i8 {
-        2 * self.overclocking_steps(recipe_voltage)
+    /// [`Voltage::Low`] with [`Overclocking::Normal`].
+    pub fn eu_factor_log2(self, recipe_voltage: Voltage, overclocking: Overclocking) -> i8 {
+        overclocking.eu_factor_log2_per_step() * self.overclocking_steps(recipe_voltage)
     }
 
     /// The number of overclocking steps from the given `recipe_voltage`.
@@ -384,3 +1109,312 @@ impl FromStr for Voltage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::recipe::Machine;
+
+    fn lv_recipe() -> Recipe {
+        Recipe {
+            ticks: NonZeroU64::new(100).unwrap(),
+            eu_per_tick: -30,
+            ..Recipe::new(Machine {
+                name: "Large Chemical Reactor".into(),
+            })
+        }
+    }
+
+    fn hv_machines(parallels: u64) -> ClockedMachines {
+        ClockedMachines {
+            machines: [(
+                ClockedMachine::new(Voltage::High)
+                    .with_parallels(NonZeroU64::new(parallels).unwrap()),
+                NonZeroU64::MIN,
+            )]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parallels_are_chosen_before_overclocking() {
+        let recipe = lv_recipe();
+        let clocked_machines = hv_machines(16);
+        let clocked_machine = *clocked_machines.machines.keys().next().unwrap();
+
+        assert_eq!(
+            clocked_machines
+                .effective_parallels(&clocked_machine, Voltage::Low, &recipe)
+                .unwrap()
+                .get(),
+            16
+        );
+        assert_eq!(
+            clocked_machines
+                .speed_factor(Voltage::Low, &recipe)
+                .unwrap(),
+            Rational::from(16)
+        );
+        assert_eq!(
+            clocked_machines.eu_per_tick(Voltage::Low, &recipe).unwrap(),
+            Integer::from(-480)
+        );
+    }
+
+    #[test]
+    fn underclocking_limits_parallels() {
+        let recipe = lv_recipe();
+        let clocked_machine = ClockedMachine::with_underclocking(Voltage::High, Voltage::Medium)
+            .with_parallels(NonZeroU64::new(16).unwrap());
+        let clocked_machines = ClockedMachines {
+            machines: [(clocked_machine, NonZeroU64::MIN)].into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            clocked_machines
+                .effective_parallels(&clocked_machine, Voltage::Low, &recipe)
+                .unwrap()
+                .get(),
+            4
+        );
+        assert_eq!(
+            clocked_machines
+                .speed_factor(Voltage::Low, &recipe)
+                .unwrap(),
+            Rational::from(4)
+        );
+        assert_eq!(
+            clocked_machines.eu_per_tick(Voltage::Low, &recipe).unwrap(),
+            Integer::from(-120)
+        );
+    }
+
+    fn voltage(eu_per_tick: u64, amperage: u64) -> Voltage {
+        Voltage::from_eu_per_tick(
+            NonZeroU64::new(eu_per_tick).unwrap(),
+            NonZeroU64::new(amperage).unwrap(),
+        )
+    }
+
+    #[test]
+    fn voltage_is_rounded_up_to_the_next_tier() {
+        assert_eq!(voltage(1, 1), Voltage::UltraLow);
+        assert_eq!(voltage(8, 1), Voltage::UltraLow);
+        assert_eq!(voltage(9, 1), Voltage::Low);
+        assert_eq!(voltage(32, 1), Voltage::Low);
+        assert_eq!(voltage(33, 1), Voltage::Medium);
+        assert_eq!(voltage(128, 1), Voltage::Medium);
+        assert_eq!(voltage(129, 1), Voltage::High);
+        assert_eq!(voltage(512, 1), Voltage::High);
+        assert_eq!(voltage(513, 1), Voltage::Extreme);
+    }
+
+    #[test]
+    fn voltage_is_split_across_amperage() {
+        assert_eq!(voltage(64, 2), Voltage::Low);
+        assert_eq!(voltage(66, 2), Voltage::Medium);
+        assert_eq!(voltage(256, 2), Voltage::Medium);
+        assert_eq!(voltage(257, 2), Voltage::High);
+    }
+
+    #[test]
+    fn machines_below_the_recipe_voltage_are_rejected() {
+        let mv_machines = ClockedMachines {
+            machines: [(ClockedMachine::new(Voltage::Medium), NonZeroU64::MIN)].into(),
+            ..Default::default()
+        };
+        let lv_machines = ClockedMachines {
+            machines: [(ClockedMachine::new(Voltage::Low), NonZeroU64::MIN)].into(),
+            ..Default::default()
+        };
+
+        for (clocked_machines, eu_per_tick, required, available) in [
+            (mv_machines, -240, Voltage::High, Voltage::Medium),
+            (lv_machines, -40, Voltage::Medium, Voltage::Low),
+        ] {
+            let recipe = Recipe {
+                eu_per_tick,
+                ..lv_recipe()
+            };
+            assert_eq!(recipe.voltage(), Some(required));
+            assert_eq!(
+                clocked_machines.speed_factor(required, &recipe),
+                Err(MachinePowerError::InsufficientTier {
+                    required,
+                    available,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn steam_machines_reject_multiple_amps() {
+        let steam_machines = SteamMachines {
+            steam: [(SteamTier::Bronze, NonZeroU64::MIN)].into(),
+        };
+        let recipe = Recipe {
+            amperage: NonZeroU64::new(2).unwrap(),
+            ..lv_recipe()
+        };
+
+        assert_eq!(
+            steam_machines.speed_factor(Voltage::Low, &recipe),
+            Err(MachinePowerError::InsufficientAmperage {
+                required: 2,
+                available: 1,
+            })
+        );
+        assert_eq!(
+            steam_machines.speed_factor(Voltage::Low, &lv_recipe()),
+            Ok(Rational::from_unsigneds(1u64, 2))
+        );
+    }
+
+    #[test]
+    fn coils_gain_heat_per_tier_above_mv() {
+        assert_eq!(Coil::Cupronickel.heat_at(Voltage::Low), 1801);
+        assert_eq!(Coil::Cupronickel.heat_at(Voltage::Medium), 1801);
+        assert_eq!(Coil::Cupronickel.heat_at(Voltage::High), 1901);
+        assert_eq!(Coil::Kanthal.heat_at(Voltage::Extreme), 2901);
+    }
+
+    #[test]
+    fn heat_bonus_is_granted_per_excess_heat() {
+        // cupronickel coils reach 2001K at EV
+        let heat_bonus = |excess_heat: u64| {
+            let recipe = Recipe {
+                heat: NonZeroU64::new(2001 - excess_heat),
+                ..lv_recipe()
+            };
+            HeatBonus::new(Some(Coil::Cupronickel), Voltage::Extreme, &recipe).unwrap()
+        };
+
+        assert_eq!(heat_bonus(0), HeatBonus::default());
+        assert_eq!(heat_bonus(899), HeatBonus::default());
+        assert_eq!(
+            heat_bonus(900),
+            HeatBonus {
+                eu_discounts: 1,
+                perfect_overclocks: 0,
+            }
+        );
+        assert_eq!(
+            heat_bonus(1799),
+            HeatBonus {
+                eu_discounts: 1,
+                perfect_overclocks: 0,
+            }
+        );
+        assert_eq!(
+            heat_bonus(1800),
+            HeatBonus {
+                eu_discounts: 2,
+                perfect_overclocks: 1,
+            }
+        );
+        assert_eq!(heat_bonus(900).discount(-100), Integer::from(-95));
+        assert_eq!(heat_bonus(1800).discount(-100), Integer::from(-90));
+    }
+
+    #[test]
+    fn insufficient_heat_is_rejected() {
+        let recipe = Recipe {
+            heat: NonZeroU64::new(2002),
+            ..lv_recipe()
+        };
+
+        assert_eq!(
+            HeatBonus::new(Some(Coil::Cupronickel), Voltage::Extreme, &recipe),
+            Err(MachinePowerError::InsufficientHeat {
+                required: 2002,
+                available: 2001,
+            })
+        );
+    }
+
+    fn overclock_twice(recipe: &Recipe) -> Overclock {
+        Overclock::new(
+            Voltage::High,
+            NonZeroU64::MIN,
+            Voltage::Low,
+            recipe,
+            HeatBonus::default(),
+            NonZeroU64::MIN,
+        )
+    }
+
+    #[test]
+    fn overclocking_stops_at_a_single_tick() {
+        let recipe = Recipe {
+            ticks: NonZeroU64::new(3).unwrap(),
+            ..lv_recipe()
+        };
+
+        let overclock = overclock_twice(&recipe);
+        assert_eq!(overclock.ticks, NonZeroU64::MIN);
+        assert_eq!(overclock.parallels, NonZeroU64::MIN);
+        assert_eq!(overclock.steps, 1);
+        assert_eq!(overclock.speed_factor(recipe.ticks), Rational::from(3));
+        assert_eq!(overclock.eu_factor_log2(recipe.overclocking), 2);
+    }
+
+    #[test]
+    fn subtick_parallels_continue_overclocking_past_a_single_tick() {
+        let recipe = Recipe {
+            ticks: NonZeroU64::new(3).unwrap(),
+            subtick_parallels: true,
+            ..lv_recipe()
+        };
+
+        let overclock = overclock_twice(&recipe);
+        assert_eq!(overclock.ticks, NonZeroU64::MIN);
+        assert_eq!(overclock.parallels, NonZeroU64::new(2).unwrap());
+        assert_eq!(overclock.steps, 2);
+        assert_eq!(overclock.speed_factor(recipe.ticks), Rational::from(6));
+        assert_eq!(overclock.eu_factor_log2(recipe.overclocking), 4);
+    }
+
+    #[test]
+    fn overclocking_stays_within_machine_amperage() {
+        let recipe = Recipe {
+            eu_per_tick: -240,
+            amperage: NonZeroU64::new(2).unwrap(),
+            ..lv_recipe()
+        };
+        let clocked_machines = hv_machines(1);
+
+        // 240 EU/t already need HV at 1A, so there is nothing left for overclocking.
+        assert_eq!(
+            clocked_machines
+                .speed_factor(Voltage::Medium, &recipe)
+                .unwrap(),
+            Rational::ONE
+        );
+        assert_eq!(
+            clocked_machines
+                .eu_per_tick(Voltage::Medium, &recipe)
+                .unwrap(),
+            Integer::from(-240)
+        );
+    }
+
+    #[test]
+    fn remaining_eu_is_used_for_overclocking() {
+        let recipe = lv_recipe();
+        let clocked_machines = hv_machines(4);
+
+        // 4 parallels at 120 EU/t fit into MV, leaving a single overclock to HV.
+        assert_eq!(
+            clocked_machines
+                .speed_factor(Voltage::Low, &recipe)
+                .unwrap(),
+            Rational::from(8)
+        );
+        assert_eq!(
+            clocked_machines.eu_per_tick(Voltage::Low, &recipe).unwrap(),
+            Integer::from(-480)
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use std::{collections::BTreeMap, num::NonZeroU64};
+
+use itertools::Itertools;
+use log::debug;
+use malachite::{
+    num::{
+        basic::traits::{One, Zero},
+        conversion::traits::RoundingFrom,
+    },
+    rounding_modes::RoundingMode,
+    Integer, Rational,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    machine::{ClockedMachines, MachineGroup, Machines, SteamMachines},
+    processing_chain::{ProcessingChain, Setup},
+};
+
+/// Rounds the exact speeds of a [`ProcessingChain`] to whole machines per [`Setup`].
+///
+/// Every [`Setup`] keeps the kinds of machines it already has, e.g. its
+/// [`ClockedMachine`](super::machine::ClockedMachine)s, but may mix up to two of them, e.g. 3×
+/// MV + 1× LV.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MachineCountSolver {
+    /// How much slower than the fastest [`Setup`] any other [`Setup`] may run.
+    ///
+    /// E.g. `1/20` requires every [`Setup`] to run at 95% or more.
+    #[serde(with = "super::serde_util::rational")]
+    pub max_imbalance: Rational,
+    /// The maximum number of machines per [`Setup`].
+    pub max_machines: NonZeroU64,
+}
+
+impl Default for MachineCountSolver {
+    fn default() -> Self {
+        Self {
+            max_imbalance: Rational::from_unsigneds(1u8, 20),
+            max_machines: NonZeroU64::new(64).expect("should not be zero"),
+        }
+    }
+}
+
+impl MachineCountSolver {
+    /// Finds the smallest machine counts that stay within [`Self::max_imbalance`].
+    ///
+    /// Returns the new [`Machines`] for each [`Setup`] or [`None`] if the [`Setup`] should be
+    /// left unchanged, e.g. because it has a
+    /// [`MachinePowerError`](super::machine::MachinePowerError), has no machines or is not
+    /// running at all.
+    ///
+    /// Instead of trying every ratio, the search jumps straight to the smallest ratio that every
+    /// [`Setup`] seen so far can reach, which usually settles after a few steps.
+    pub fn solve(
+        &self,
+        processing_chain: &ProcessingChain,
+    ) -> Result<Vec<Option<Machines>>, MachineCountError> {
+        let setups = processing_chain.setups();
+        debug!("Solving machine counts for {} setups.", setups.len());
+
+        let demands = setups
+            .iter()
+            .zip_eq(processing_chain.weighted_speeds().speeds())
+            .map(|(setup, speed)| Demand::new(setup, speed, self.max_machines))
+            .collect_vec();
+
+        let max_factor = if self.max_imbalance < Rational::ONE {
+            Some(Rational::ONE / (Rational::ONE - &self.max_imbalance))
+        } else {
+            None
+        };
+
+        let no_solution = MachineCountError::NoSolution {
+            max_machines: self.max_machines.get(),
+        };
+
+        let mut ratio = Rational::ZERO;
+        loop {
+            let mut next_ratio = ratio.clone();
+            for demand in demands.iter().flatten() {
+                let demand_ratio = demand
+                    .next_ratio(&ratio, max_factor.as_ref())
+                    .ok_or_else(|| no_solution.clone())?;
+                next_ratio = next_ratio.max(demand_ratio);
+            }
+            if next_ratio == ratio {
+                break;
+            }
+            ratio = next_ratio;
+        }
+        debug!("Found machine counts at ratio {ratio}.");
+
+        demands
+            .iter()
+            .zip_eq(setups)
+            .map(|(demand, setup)| {
+                demand
+                    .as_ref()
+                    .map(|demand| {
+                        let option = demand
+                            .best_option(&ratio, max_factor.as_ref())
+                            .ok_or_else(|| no_solution.clone())?;
+                        Ok(option.machines(&setup.machines))
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Error)]
+pub enum MachineCountError {
+    #[error("no solution with up to {max_machines} machines per setup")]
+    NoSolution { max_machines: u64 },
+}
+
+/// The capacity a [`Setup`] needs relative to all other [`Setup`]s.
+struct Demand {
+    required: Rational,
+    /// The speed factor of a single machine of each [`MachineGroup`].
+    ///
+    /// Groups that do not contribute any speed are left out.
+    unit_speed_factors: Vec<(MachineGroup, Rational)>,
+    max_machines: u64,
+}
+
+impl Demand {
+    /// Returns [`None`] if the [`Setup`] does not need any capacity or has no machines to
+    /// provide it.
+    fn new(setup: &Setup, speed: &Rational, max_machines: NonZeroU64) -> Option<Self> {
+        let required = setup.speed_factor().ok()? * speed;
+        if required == 0 {
+            return None;
+        }
+
+        let unit_speed_factors = setup
+            .machines
+            .speed_factors_per_machine(&setup.recipe)
+            .ok()?
+            .into_iter()
+            .filter(|(_, speed_factor)| *speed_factor != 0)
+            .collect_vec();
+
+        (!unit_speed_factors.is_empty()).then_some(Self {
+            required,
+            unit_speed_factors,
+            max_machines: max_machines.get(),
+        })
+    }
+
+    /// The smallest ratio from `ratio` onwards at which a [`CountOption`] runs between `ratio`
+    /// and `ratio * max_factor` times the required capacity.
+    fn next_ratio(&self, ratio: &Rational, max_factor: Option<&Rational>) -> Option<Rational> {
+        let min_capacity = ratio * &self.required;
+        let capacity = self
+            .minimal_options(&min_capacity)
+            .into_iter()
+            .map(|option| option.capacity)
+            .min()?;
+        Some(match max_factor {
+            Some(max_factor) if capacity > &min_capacity * max_factor => {
+                capacity / (&self.required * max_factor)
+            }
+            _ => ratio.clone(),
+        })
+    }
+
+    /// The [`CountOption`] with the fewest machines that runs between `ratio` and
+    /// `ratio * max_factor` times the required capacity.
+    fn best_option(&self, ratio: &Rational, max_factor: Option<&Rational>) -> Option<CountOption> {
+        let min_capacity = ratio * &self.required;
+        let max_capacity = max_factor.map(|max_factor| &min_capacity * max_factor);
+        self.minimal_options(&min_capacity)
+            .into_iter()
+            .filter(|option| {
+                max_capacity
+                    .as_ref()
+                    .is_none_or(|max_capacity| option.capacity <= *max_capacity)
+            })
+            .min_by(|a, b| {
+                a.total()
+                    .cmp(&b.total())
+                    .then_with(|| a.capacity.cmp(&b.capacity))
+            })
+    }
+
+    /// All [`CountOption`]s with at least `min_capacity` that would drop below it with one
+    /// machine less.
+    ///
+    /// Mixing two kinds of machines only needs the fewest second machines for each count of
+    /// first machines, so this stays linear in [`MachineCountSolver::max_machines`].
+    fn minimal_options(&self, min_capacity: &Rational) -> Vec<CountOption> {
+        let mut options = Vec::new();
+        for (position, (first, first_speed_factor)) in self.unit_speed_factors.iter().enumerate() {
+            let first_count = self.min_count(min_capacity, first_speed_factor);
+            if first_count <= self.max_machines {
+                options.push(CountOption::new(vec![(
+                    (*first, first_count),
+                    first_speed_factor,
+                )]));
+            }
+
+            for (second, second_speed_factor) in &self.unit_speed_factors[position + 1..] {
+                for partial_count in 1..first_count.min(self.max_machines) {
+                    let remaining =
+                        min_capacity - first_speed_factor * Rational::from(partial_count);
+                    let second_count = self.min_count(&remaining, second_speed_factor);
+                    if partial_count + second_count <= self.max_machines {
+                        options.push(CountOption::new(vec![
+                            ((*first, partial_count), first_speed_factor),
+                            ((*second, second_count), second_speed_factor),
+                        ]));
+                    }
+                }
+            }
+        }
+        options
+    }
+
+    /// The fewest machines, but at least one, with at least `min_capacity`.
+    ///
+    /// Anything above [`Self::max_machines`] is returned as one more than that.
+    fn min_count(&self, min_capacity: &Rational, speed_factor: &Rational) -> u64 {
+        let (count, _) = Integer::rounding_from(min_capacity / speed_factor, RoundingMode::Ceiling);
+        if count > self.max_machines {
+            self.max_machines.saturating_add(1)
+        } else {
+            u64::try_from(&count.max(Integer::ONE)).expect("count should fit into u64")
+        }
+    }
+}
+
+/// Machine counts for a [`Setup`] by [`MachineGroup`].
+struct CountOption {
+    capacity: Rational,
+    counts: Vec<(MachineGroup, u64)>,
+}
+
+impl CountOption {
+    fn new(counts: Vec<((MachineGroup, u64), &Rational)>) -> Self {
+        Self {
+            capacity: counts
+                .iter()
+                .map(|((_, count), speed_factor)| *speed_factor * Rational::from(*count))
+                .sum(),
+            counts: counts.into_iter().map(|(count, _)| count).collect(),
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    fn machines(&self, machines: &Machines) -> Machines {
+        match machines {
+            Machines::Eco(_) => Machines::Eco(self.total()),
+            Machines::Steam(_) => Machines::Steam(SteamMachines {
+                steam: self.counts(|group| match group {
+                    MachineGroup::Steam(tier) => Some(tier),
+                    _ => None,
+                }),
+            }),
+            Machines::Power(clocked_machines) => Machines::Power(ClockedMachines {
+                machines: self.counts(|group| match group {
+                    MachineGroup::Power(clocked_machine) => Some(clocked_machine),
+                    _ => None,
+                }),
+                ..clocked_machines.clone()
+            }),
+        }
+    }
+
+    fn counts<K: Ord>(&self, key: impl Fn(MachineGroup) -> Option<K>) -> BTreeMap<K, NonZeroU64> {
+        self.counts
+            .iter()
+            .filter_map(|&(group, count)| Some((key(group)?, NonZeroU64::new(count)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        machine::{ClockedMachine, Voltage},
+        test_util::{processing_chain, setup},
+    };
+
+    fn powered(tiers: &[(Voltage, u64)]) -> Machines {
+        Machines::Power(ClockedMachines {
+            machines: tiers
+                .iter()
+                .map(|&(tier, count)| (ClockedMachine::new(tier), NonZeroU64::new(count).unwrap()))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Like [`setup`] but running a 30 EU/t recipe on the given `machines`.
+    fn powered_setup(
+        consumed: Option<(&str, u64)>,
+        produced: (&str, u64),
+        machines: Machines,
+    ) -> Setup {
+        let mut setup = setup(consumed, produced);
+        setup.recipe.eu_per_tick = -30;
+        setup.machines = machines;
+        setup
+    }
+
+    #[test]
+    fn single_tier_divides_evenly() {
+        let lv = powered(&[(Voltage::Low, 1)]);
+        let processing_chain = processing_chain(vec![
+            powered_setup(None, ("Plate", 2), lv.clone()),
+            powered_setup(Some(("Plate", 1)), ("Gear", 1), lv),
+        ]);
+
+        assert_eq!(
+            MachineCountSolver::default().solve(&processing_chain),
+            Ok(vec![
+                Some(powered(&[(Voltage::Low, 1)])),
+                Some(powered(&[(Voltage::Low, 2)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn two_tiers_are_mixed_within_max_imbalance() {
+        // the gears need 5× the capacity of the plates, which MV machines alone overshoot
+        let processing_chain = processing_chain(vec![
+            powered_setup(None, ("Plate", 5), powered(&[(Voltage::Low, 1)])),
+            powered_setup(
+                Some(("Plate", 1)),
+                ("Gear", 1),
+                powered(&[(Voltage::Low, 1), (Voltage::Medium, 1)]),
+            ),
+        ]);
+
+        assert_eq!(
+            MachineCountSolver::default().solve(&processing_chain),
+            Ok(vec![
+                Some(powered(&[(Voltage::Low, 1)])),
+                Some(powered(&[(Voltage::Low, 1), (Voltage::Medium, 2)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn max_machines_is_not_exceeded() {
+        let lv = powered(&[(Voltage::Low, 1)]);
+        let processing_chain = processing_chain(vec![
+            powered_setup(None, ("Plate", 3), lv.clone()),
+            powered_setup(Some(("Plate", 1)), ("Gear", 1), lv),
+        ]);
+        let solver = MachineCountSolver {
+            max_machines: NonZeroU64::new(2).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            solver.solve(&processing_chain),
+            Err(MachineCountError::NoSolution { max_machines: 2 })
+        );
+    }
+
+    #[test]
+    fn setups_without_machines_are_unchanged() {
+        let processing_chain = processing_chain(vec![
+            powered_setup(None, ("Plate", 1), powered(&[(Voltage::Low, 1)])),
+            powered_setup(None, ("Rod", 1), powered(&[])),
+        ]);
+
+        assert_eq!(
+            MachineCountSolver::default().solve(&processing_chain),
+            Ok(vec![Some(powered(&[(Voltage::Low, 1)])), None])
+        );
+    }
+}
@@ -1,22 +1,31 @@
 use std::{
-    cell::{LazyCell, OnceCell},
+    cell::OnceCell,
     collections::{BTreeMap, BTreeSet},
+    num::NonZeroU64,
 };
 
 use bitvec::vec::BitVec;
 use itertools::Itertools;
 use log::debug;
 use malachite::{
-    num::basic::traits::{One, Zero},
-    Rational,
+    num::{
+        arithmetic::traits::Abs,
+        basic::traits::{One, Zero},
+    },
+    Integer, Rational,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{ser::PrettyFormatter, Serializer};
 
 use super::{
-    machine::{MachinePowerError, Machines},
-    recipe::{Machine, Product, Recipe},
+    machine::{MachineGroup, MachinePowerError, Machines},
+    recipe::{Machine, Product, ProductCount, Recipe},
+    serde_util::{is_false, is_one, one, rational},
+};
+use crate::math::{
+    nullspace::nullspace,
+    simplex::{maximize, Constraint, Relation, SimplexError},
 };
-use crate::math::nullspace::nullspace;
 
 /// Consists of various machines that are processing [`Product`]s using specific [`Recipe`]s.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -32,6 +41,18 @@ pub struct ProcessingChain {
     /// implicitly, as the producing/consuming machines would not be able to run at all.
     #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
     explicit_io: BTreeSet<Product>,
+    /// Whether EU is treated like any other [`Product`] when solving for machine speeds.
+    ///
+    /// Generators are then throttled to exactly power all consumers of the chain (or vice versa).
+    /// Like other [`Product`]s, EU is only balanced if it is both produced and consumed.
+    #[serde(default, skip_serializing_if = "is_false")]
+    balance_eu: bool,
+    /// The rate at which a single [`Product`] should be processed by the entire chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<Target>,
+    /// If set, speeds are optimized using a [`LinearProgram`] instead of being balanced exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    linear_program: Option<LinearProgram>,
     /// Caches various information about the [`ProcessingChain`].
     ///
     /// Whenever fields are updated relevant cached values are invalidated.
@@ -39,7 +60,44 @@ pub struct ProcessingChain {
     cache: Cache,
 }
 
+impl PartialEq for ProcessingChain {
+    /// Compares everything except the cached values.
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            setups,
+            explicit_io,
+            balance_eu,
+            target,
+            linear_program,
+            cache: _,
+        } = self;
+        *setups == other.setups
+            && *explicit_io == other.explicit_io
+            && *balance_eu == other.balance_eu
+            && *target == other.target
+            && *linear_program == other.linear_program
+    }
+}
+
+impl Eq for ProcessingChain {}
+
 impl ProcessingChain {
+    /// Whether there are no [`Setup`]s at all.
+    pub fn is_empty(&self) -> bool {
+        self.setups.is_empty()
+    }
+
+    /// Serializes the [`ProcessingChain`] with the same indentation as hand-written files.
+    pub fn to_json(&self) -> String {
+        let mut json = Vec::new();
+        let formatter = PrettyFormatter::with_indent(b"    ");
+        let mut serializer = Serializer::with_formatter(&mut json, formatter);
+        self.serialize(&mut serializer)
+            .expect("processing chain should be serializable");
+        json.push(b'\n');
+        String::from_utf8(json).expect("json should be valid utf-8")
+    }
+
     pub fn setups(&self) -> &[Setup] {
         &self.setups
     }
@@ -57,13 +115,58 @@ impl ProcessingChain {
         &mut self.setups[index].recipe.catalysts
     }
 
-    /// Updates a [`Setup::weight`], which only invalidates the cached [`WeightedSpeeds`].
+    /// Updates a [`Setup::weight`], which only invalidates the cached [`WeightedSpeeds`] and
+    /// everything derived from the [`Target`].
     pub fn set_weight(&mut self, index: usize, weight: Weight) {
         self.cache.weighted_speeds.take();
+        self.cache.clear_target();
         self.setups[index].weight = weight;
     }
 
-    pub fn explicit_ui(&self) -> &BTreeSet<Product> {
+    /// The share of each [`Product`] the [`Setup`] at `index` gets when all [`Setup`]s run at
+    /// their [`WeightedSpeeds`].
+    ///
+    /// Only includes [`Product`]s that are also consumed or produced by another [`Setup`].
+    /// Shares of consumed [`Product`]s are negative.
+    pub fn weight_shares(&self, index: usize) -> Vec<(&Product, Rational)> {
+        let products_per_sec = self
+            .setups
+            .iter()
+            .zip_eq(self.weighted_speeds().speeds())
+            .map(|(setup, speed)| {
+                setup
+                    .products_per_sec_filter_ok()
+                    .map(|(product, amount)| {
+                        let weighted_amount = &amount * speed;
+                        (product, (amount, weighted_amount))
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .collect_vec();
+
+        products_per_sec[index]
+            .iter()
+            .filter_map(|(&product, (amount, weighted_amount))| {
+                let mut shared = false;
+                let mut total = Rational::ZERO;
+                for (other_index, other) in products_per_sec.iter().enumerate() {
+                    if let Some((other_amount, other_weighted_amount)) = other.get(product) {
+                        // only machines that consume or produce it alike share it
+                        if (*other_amount < 0) == (*amount < 0) {
+                            shared |= other_index != index;
+                            total += other_weighted_amount;
+                        }
+                    }
+                }
+                (shared && total != 0).then(|| {
+                    let share = weighted_amount / total;
+                    (product, if *amount < 0 { -share } else { share })
+                })
+            })
+            .collect()
+    }
+
+    pub fn explicit_io(&self) -> &BTreeSet<Product> {
         &self.explicit_io
     }
 
@@ -72,11 +175,106 @@ impl ProcessingChain {
         &mut self.explicit_io
     }
 
+    pub fn balance_eu(&self) -> bool {
+        self.balance_eu
+    }
+
+    pub fn set_balance_eu(&mut self, balance_eu: bool) {
+        self.cache = Cache::default();
+        self.balance_eu = balance_eu;
+    }
+
+    pub fn linear_program(&self) -> Option<&LinearProgram> {
+        self.linear_program.as_ref()
+    }
+
+    pub fn linear_program_mut(&mut self) -> &mut Option<LinearProgram> {
+        self.cache = Cache::default();
+        &mut self.linear_program
+    }
+
+    /// Whether the given `product` is forced to net-zero when solving for machine speeds.
+    ///
+    /// This is the case for all [`Product`]s that are both consumed and produced, unless they are
+    /// part of [`Self::explicit_io`].
+    pub fn is_balanced(&self, product: &Product) -> bool {
+        !self.explicit_io.contains(product)
+            && self.setups.iter().any(|setup| setup.consumes(product))
+            && self.setups.iter().any(|setup| setup.produces(product))
+    }
+
+    pub fn target(&self) -> Option<&Target> {
+        self.target.as_ref()
+    }
+
+    /// Updates the [`Target`], which only invalidates values derived from it.
+    pub fn set_target(&mut self, target: Option<Target>) {
+        self.cache.clear_target();
+        self.target = target;
+    }
+
+    /// Scales the [`WeightedSpeeds`] so that the [`Target`] is met exactly.
+    ///
+    /// Returns [`None`] if there is no [`Target`] or its [`Product`] is balanced to net-zero.
+    pub fn target_speeds(&self) -> Option<&[Rational]> {
+        self.cache
+            .target_speeds
+            .get_or_init(|| {
+                let target = self.target.as_ref()?;
+                let weighted_speeds = self.weighted_speeds();
+                let amount = self
+                    .products_with_speeds(weighted_speeds)
+                    .products_per_sec
+                    .remove(&target.product)
+                    .filter(|amount| *amount != 0)?;
+                let scale = target.per_sec() / amount.abs();
+                Some(
+                    weighted_speeds
+                        .speeds
+                        .iter()
+                        .map(|speed| speed * &scale)
+                        .collect(),
+                )
+            })
+            .as_deref()
+    }
+
+    /// The exact number of machines each [`Setup`] requires to meet the [`Target`].
+    ///
+    /// Lists how many machines of each [`MachineGroup`] would be required if the [`Setup`]
+    /// consisted of only that group, since e.g. an HV machine does the work of four LV machines.
+    /// [`Setup`]s with a [`MachinePowerError`] have no entries.
+    pub fn required_machines(&self) -> Option<&[RequiredMachines]> {
+        self.cache
+            .required_machines
+            .get_or_init(|| {
+                Some(
+                    self.target_speeds()?
+                        .iter()
+                        .zip_eq(&self.setups)
+                        .map(|(speed, setup)| {
+                            let Ok(speed_factor) = setup.speed_factor() else {
+                                return Vec::new();
+                            };
+                            let required_speed_factor = speed * speed_factor;
+                            setup
+                                .machines
+                                .speed_factors_per_machine(&setup.recipe)
+                                .into_iter()
+                                .flatten()
+                                .map(|(group, speed_factor_per_machine)| {
+                                    (group, &required_speed_factor / speed_factor_per_machine)
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                )
+            })
+            .as_deref()
+    }
+
     pub fn products(&self) -> BTreeSet<&Product> {
-        self.setups
-            .iter()
-            .flat_map(|setup| setup.recipe.products())
-            .collect()
+        self.setups.iter().flat_map(Setup::products).collect()
     }
 
     /// Returns the total [`Products`] assuming all machines are running at normal speed.
@@ -90,21 +288,59 @@ impl ProcessingChain {
         self.products_with_speed_callback(|index| &weighted_speeds.speeds[index])
     }
 
+    /// Returns the total [`Products`] assuming recipes are running at [`Self::target_speeds`].
+    pub fn products_with_target_speeds(&self) -> Option<Products> {
+        let target_speeds = self.target_speeds()?;
+        Some(self.products_with_speed_callback(|index| &target_speeds[index]))
+    }
+
     pub fn speeds(&self) -> &Speeds {
         self.cache.speeds.get_or_init(|| Speeds::new(self))
     }
 
+    /// The result of the [`LinearProgram`], if there is one.
+    pub fn optimized_speeds(&self) -> Option<&Result<Vec<Rational>, SimplexError>> {
+        let linear_program = self.linear_program.as_ref()?;
+        Some(
+            self.cache
+                .optimized_speeds
+                .get_or_init(|| linear_program.solve(self)),
+        )
+    }
+
+    /// The speeds of all [`Setup`]s, either weighted or optimized by a [`LinearProgram`].
+    ///
+    /// If the [`LinearProgram`] has no solution, all [`Setup`]s are stopped.
     pub fn weighted_speeds(&self) -> &WeightedSpeeds {
         self.cache
             .weighted_speeds
-            .get_or_init(|| WeightedSpeeds::new(self.speeds(), &self.setups))
+            .get_or_init(|| match self.optimized_speeds() {
+                Some(Ok(speeds)) => WeightedSpeeds {
+                    speeds: speeds.clone(),
+                },
+                Some(Err(_)) => WeightedSpeeds {
+                    speeds: vec![Rational::ZERO; self.setups.len()],
+                },
+                None => WeightedSpeeds::new(self.speeds(), &self.setups),
+            })
     }
 
+    /// Replaces `old` with `new` everywhere, merging both if `new` already exists.
     pub fn replace_product(&mut self, old: &Product, new: Product) {
         for setup in self.setups_mut() {
             setup.recipe.replace_product(old, &new);
         }
 
+        if let Some(target) = &mut self.target {
+            if target.product == *old {
+                target.product = new.clone();
+            }
+        }
+
+        if let Some(linear_program) = self.linear_program_mut() {
+            linear_program.replace_product(old, &new);
+        }
+
         if self.explicit_io_mut().remove(old) {
             self.explicit_io_mut().insert(new);
         }
@@ -127,7 +363,7 @@ impl ProcessingChain {
                     *acc.products_per_sec.entry(product.clone()).or_default() += count * speed;
                 }
 
-                if let Ok(eu_per_tick) = setup.machines.eu_per_tick(setup.recipe.eu_per_tick) {
+                if let Ok(eu_per_tick) = setup.eu_per_tick() {
                     acc.eu_per_tick += Rational::from(eu_per_tick) * speed;
                 }
 
@@ -136,6 +372,36 @@ impl ProcessingChain {
     }
 }
 
+/// How many machines of each [`MachineGroup`] a [`Setup`] requires to meet the [`Target`]; see
+/// [`ProcessingChain::required_machines`].
+pub type RequiredMachines = Vec<(MachineGroup, Rational)>;
+
+/// A desired rate at which a [`Product`] is processed, e.g. `2 L` of Polyethylene per second.
+///
+/// Applies to whichever direction the [`Product`] flows, i.e. output or input of the chain.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub product: Product,
+    pub count: NonZeroU64,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub seconds: NonZeroU64,
+}
+
+impl Target {
+    pub fn new(product: Product) -> Self {
+        Self {
+            product,
+            count: NonZeroU64::MIN,
+            seconds: NonZeroU64::MIN,
+        }
+    }
+
+    pub fn per_sec(&self) -> Rational {
+        Rational::from_unsigneds(self.count.get(), self.seconds.get())
+    }
+}
+
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Products {
     pub eu_per_tick: Rational,
@@ -180,7 +446,7 @@ pub struct Setup {
     /// **Note:** Setting a weight to zero effectively disables the [`Setup`],
     /// preventing the machine from contributing to the product allocation. This is useful for
     /// temporarily stopping a machine from participating in the allocation process.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Weight::is_default")]
     pub weight: Weight,
 }
 
@@ -195,29 +461,73 @@ impl Setup {
 
     /// How fast this [`Setup`] can process recipes.
     pub fn speed_factor(&self) -> Result<Rational, MachinePowerError> {
-        self.machines.speed_factor(self.recipe.voltage())
+        self.machines.speed_factor(&self.recipe)
     }
 
-    fn products_per_sec_filter_ok(&self) -> impl Iterator<Item = (&Product, Rational)> {
-        self.products_per_sec()
-            .filter_map(|(product, amount)| amount.ok().map(|amount| (product, amount)))
+    /// The EU/t of all machines in this [`Setup`].
+    pub fn eu_per_tick(&self) -> Result<Integer, MachinePowerError> {
+        self.machines.eu_per_tick(&self.recipe)
     }
 
-    fn products_per_sec(
+    /// How many of the given `product_count` are processed per second.
+    ///
+    /// Takes [`Recipe::chance_boost`] into account for overclocked machines.
+    pub fn product_count_per_sec(
         &self,
-    ) -> impl Iterator<Item = (&Product, Result<Rational, MachinePowerError>)> {
-        let speed_factor = LazyCell::new(|| self.speed_factor());
-        self.recipe
-            .products_per_sec()
-            .map(move |(product, amount)| {
-                (
-                    product,
-                    speed_factor
-                        .as_ref()
-                        .map(|speed_factor| amount * speed_factor)
-                        .map_err(|error| *error),
-                )
+        product_count: &ProductCount,
+    ) -> Result<Rational, MachinePowerError> {
+        let seconds = self.recipe.seconds();
+        Ok(self
+            .machines
+            .speed_factors_per_tier(&self.recipe)?
+            .into_iter()
+            .map(|(tiers, speed_factor)| {
+                product_count.boosted_expected_count(self.recipe.chance_boost, tiers) / &seconds
+                    * speed_factor
             })
+            .sum())
+    }
+
+    /// The [`Product`]s of the [`Recipe`] as well as [`Product::steam`] for steam machines.
+    pub fn products(&self) -> impl Iterator<Item = &Product> {
+        let steam = matches!(self.machines, Machines::Steam(_)).then(Product::steam);
+        self.recipe.products().chain(steam)
+    }
+
+    pub fn produces(&self, product: &Product) -> bool {
+        self.recipe.produces(product)
+    }
+
+    pub fn consumes(&self, product: &Product) -> bool {
+        self.recipe.consumes(product)
+            || matches!(self.machines, Machines::Steam(_)) && product == Product::steam()
+    }
+
+    /// The [`Product`]s processed per second by all machines.
+    ///
+    /// Takes [`Recipe::chance_boost`] into account for overclocked machines and includes the
+    /// [`Product::steam`] consumed by steam machines.
+    pub fn products_per_sec(&self) -> Result<BTreeMap<&Product, Rational>, MachinePowerError> {
+        let steam_per_sec = self.machines.steam_per_sec(&self.recipe)?;
+        let steam = (steam_per_sec != 0).then(|| (Product::steam(), -steam_per_sec));
+        Ok(self
+            .machines
+            .speed_factors_per_tier(&self.recipe)?
+            .into_iter()
+            .flat_map(|(tiers, speed_factor)| {
+                self.recipe
+                    .boosted_products_per_sec(tiers)
+                    .map(move |(product, amount)| (product, amount * &speed_factor))
+            })
+            .chain(steam)
+            .fold(BTreeMap::new(), |mut acc, (product, amount)| {
+                *acc.entry(product).or_default() += amount;
+                acc
+            }))
+    }
+
+    fn products_per_sec_filter_ok(&self) -> impl Iterator<Item = (&Product, Rational)> {
+        self.products_per_sec().into_iter().flatten()
     }
 }
 
@@ -225,6 +535,12 @@ impl Setup {
 #[serde(transparent)]
 pub struct Weight(pub u64);
 
+impl Weight {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 impl Default for Weight {
     fn default() -> Self {
         Self(1)
@@ -236,18 +552,38 @@ struct Cache {
     /// Does not change if only weights change.
     speeds: OnceCell<Speeds>,
     weighted_speeds: OnceCell<WeightedSpeeds>,
+    optimized_speeds: OnceCell<Result<Vec<Rational>, SimplexError>>,
+    /// Depends on the [`ProcessingChain::target`] and the [`WeightedSpeeds`].
+    target_speeds: OnceCell<Option<Vec<Rational>>>,
+    required_machines: OnceCell<Option<Vec<RequiredMachines>>>,
 }
 
-#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+impl Cache {
+    fn clear_target(&mut self) {
+        self.target_speeds.take();
+        self.required_machines.take();
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Speeds {
     weighted_setups: BitVec,
     speeds: Vec<Rational>,
+    /// What each row of [`Self::matrix`] balances.
+    rows: Vec<Balanced>,
+    /// The matrix that was used to calculate the nullspace, with one column per [`Setup`].
+    matrix: Vec<Rational>,
+    /// See [`Diagnostics::components`].
+    components: Vec<Vec<usize>>,
+    diagnostics: OnceCell<Diagnostics>,
 }
 
 impl Speeds {
-    /// TODO
+    /// Finds the speeds at which every balanced [`Product`] of the chain nets to zero.
     ///
     /// Any [`Setup`]s with a [`MachinePowerError`] are ignored.
+    ///
+    /// If [`ProcessingChain::balance_eu`] is set, EU is added as an additional virtual product.
     fn new(processing_chain: &ProcessingChain) -> Self {
         let setups_len = processing_chain.setups.len();
         let explicit_io_len = processing_chain.explicit_io.len();
@@ -265,20 +601,16 @@ impl Speeds {
             })
             .collect_vec();
 
-        let matrix = processing_chain
+        let products = processing_chain
             .products()
             .into_iter()
-            .filter(|product| {
-                !processing_chain.explicit_io.contains(product)
-                    && processing_chain
-                        .setups
-                        .iter()
-                        .any(|setup| setup.recipe.consumes(product))
-                    && processing_chain
-                        .setups
-                        .iter()
-                        .any(|setup| setup.recipe.produces(product))
-            })
+            .filter(|product| processing_chain.is_balanced(product))
+            .collect_vec();
+
+        let eu_row = Self::eu_row(processing_chain);
+
+        let matrix = products
+            .iter()
             .flat_map(|product| {
                 (0..processing_chains).map(|setup_index| {
                     setup_products_per_sec[setup_index]
@@ -287,14 +619,188 @@ impl Speeds {
                         .unwrap_or_default()
                 })
             })
+            .chain(eu_row.iter().flatten().cloned())
             .collect_vec();
 
-        let (weighted_setups, speeds) = nullspace(matrix, processing_chains);
+        let rows = products
+            .into_iter()
+            .map(|product| Balanced::Product(product.clone()))
+            .chain(eu_row.map(|_| Balanced::Eu))
+            .collect();
+
+        let (weighted_setups, speeds) = nullspace(matrix.clone(), processing_chains);
+        let components = Self::connected_components(&matrix, processing_chains);
         Self {
             weighted_setups,
             speeds,
+            rows,
+            matrix,
+            components,
+            diagnostics: OnceCell::new(),
         }
     }
+
+    /// Explains the result, e.g. why certain [`Setup`]s are not running at all.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        self.diagnostics.get_or_init(|| Diagnostics::new(self))
+    }
+
+    fn setups_len(&self) -> usize {
+        self.weighted_setups.len()
+    }
+
+    /// Groups all columns of the `matrix` that are connected via non-zero values in the same row.
+    fn connected_components(matrix: &[Rational], columns: usize) -> Vec<Vec<usize>> {
+        fn root(parents: &mut [usize], mut column: usize) -> usize {
+            while parents[column] != column {
+                parents[column] = parents[parents[column]];
+                column = parents[column];
+            }
+            column
+        }
+
+        if columns == 0 {
+            return Vec::new();
+        }
+
+        let mut parents = (0..columns).collect_vec();
+        for row in matrix.chunks_exact(columns) {
+            let mut connected = row.iter().positions(|value| *value != 0);
+            if let Some(first) = connected.next() {
+                let first = root(&mut parents, first);
+                for column in connected {
+                    let column = root(&mut parents, column);
+                    parents[column] = first;
+                }
+            }
+        }
+
+        (0..columns)
+            .into_group_map_by(|&column| root(&mut parents, column))
+            .into_values()
+            .sorted()
+            .collect()
+    }
+
+    /// The EU/t of each [`Setup`] if EU should be balanced.
+    fn eu_row(processing_chain: &ProcessingChain) -> Option<Vec<Rational>> {
+        if !processing_chain.balance_eu {
+            return None;
+        }
+
+        let eu_per_tick = processing_chain
+            .setups
+            .iter()
+            .map(|setup| setup.eu_per_tick().map(Rational::from).unwrap_or_default())
+            .collect_vec();
+
+        (eu_per_tick.iter().any(|eu| *eu < 0) && eu_per_tick.iter().any(|eu| *eu > 0))
+            .then_some(eu_per_tick)
+    }
+}
+
+/// Something that is balanced to net-zero by [`Speeds`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Balanced {
+    Product(Product),
+    /// Only if [`ProcessingChain::balance_eu`] is set.
+    Eu,
+}
+
+/// Explains how [`Speeds`] came to be.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostics {
+    /// The number of independent rows in the matrix of balanced products.
+    pub rank: usize,
+    /// One [`Setup`] index per free dimension of the nullspace.
+    ///
+    /// With more than one, the [`Setup::weight`]s decide how fast each [`Setup`] runs.
+    pub free_setups: Vec<usize>,
+    /// [`Setup`]s that can only run at zero speed, alongside what forces them to zero.
+    ///
+    /// Lists every [`Balanced`] row that would allow the [`Setup`] to run on its own if it was
+    /// removed. If no single row does so, all rows involving the [`Setup`] are listed instead.
+    pub zero_setups: BTreeMap<usize, Vec<Balanced>>,
+    /// Groups of [`Setup`] indices that do not share any [`Balanced`] rows with each other.
+    ///
+    /// Each group is normalized separately by [`WeightedSpeeds`].
+    pub components: Vec<Vec<usize>>,
+}
+
+impl Diagnostics {
+    fn new(speeds: &Speeds) -> Self {
+        let columns = speeds.setups_len();
+        let free_setups = speeds.weighted_setups.iter_ones().collect_vec();
+        let rank = columns - free_setups.len();
+
+        let rows = if columns == 0 {
+            Vec::new()
+        } else {
+            speeds.matrix.chunks_exact(columns).collect_vec()
+        };
+
+        let zero_setups = (0..columns)
+            .filter(|&setup_index| !Self::can_run(&speeds.speeds, columns, setup_index))
+            .map(|setup_index| {
+                let involved = rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, row)| row[setup_index] != 0)
+                    .map(|(row_index, _)| row_index)
+                    .collect_vec();
+
+                let blocking = involved
+                    .iter()
+                    .copied()
+                    .filter(|&removed_row_index| {
+                        let matrix = rows
+                            .iter()
+                            .enumerate()
+                            .filter(|(row_index, _)| *row_index != removed_row_index)
+                            .flat_map(|(_, row)| row.iter().cloned())
+                            .collect_vec();
+                        let (_, speeds) = nullspace(matrix, columns);
+                        Self::can_run(&speeds, columns, setup_index)
+                    })
+                    .collect_vec();
+
+                let reasons = if blocking.is_empty() {
+                    involved
+                } else {
+                    blocking
+                };
+
+                (
+                    setup_index,
+                    reasons
+                        .into_iter()
+                        .map(|row_index| speeds.rows[row_index].clone())
+                        .collect(),
+                )
+            })
+            .collect();
+
+        Self {
+            rank,
+            free_setups,
+            zero_setups,
+            components: speeds.components.clone(),
+        }
+    }
+
+    /// The index into [`Self::components`] of the group containing the given [`Setup`].
+    pub fn component(&self, setup_index: usize) -> Option<usize> {
+        self.components
+            .iter()
+            .position(|component| component.contains(&setup_index))
+    }
+
+    /// Whether any vector of the `nullspace` has a non-zero speed for the given `setup_index`.
+    fn can_run(nullspace: &[Rational], columns: usize, setup_index: usize) -> bool {
+        nullspace
+            .chunks_exact(columns)
+            .any(|speeds| speeds[setup_index] != 0)
+    }
 }
 
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -312,37 +818,352 @@ impl WeightedSpeeds {
             return Default::default();
         }
 
-        let mut speeds = speeds
+        let nullspace = speeds
             .speeds
             .chunks_exact(setups_len)
-            .zip(
-                speeds
-                    .weighted_setups
-                    .iter_ones()
-                    .map(|index| &setups[index]),
-            )
-            .fold(
-                vec![Rational::ONE; setups_len],
-                |mut acc, (speeds, setup)| {
-                    for (acc_speed, speed) in acc.iter_mut().zip_eq(speeds) {
-                        *acc_speed *= speed * Rational::from(setup.weight.0);
-                    }
-                    acc
-                },
-            );
+            .zip(speeds.weighted_setups.iter_ones())
+            .collect_vec();
+
+        let mut weighted_speeds = vec![Rational::ZERO; setups_len];
+
+        // unrelated components should neither throttle nor stop each other
+        for component in &speeds.components {
+            let component_nullspace = nullspace
+                .iter()
+                .filter(|(_, index)| component.contains(index))
+                .map(|(speeds, index)| (*speeds, &setups[*index]))
+                .collect_vec();
+            if component_nullspace.is_empty() {
+                continue;
+            }
+
+            for &index in component {
+                weighted_speeds[index] = component_nullspace
+                    .iter()
+                    .map(|(speeds, setup)| &speeds[index] * Rational::from(setup.weight.0))
+                    .product();
+            }
 
-        if let Some(max_speed) = speeds.iter().max().cloned() {
-            if max_speed != Rational::ZERO {
-                for speed in &mut speeds {
-                    *speed /= &max_speed;
+            let max_speed = component
+                .iter()
+                .map(|&index| &weighted_speeds[index])
+                .max()
+                .cloned();
+            if let Some(max_speed) = max_speed.filter(|max_speed| *max_speed > 0) {
+                for &index in component {
+                    weighted_speeds[index] /= &max_speed;
                 }
             }
         }
 
-        Self { speeds }
+        Self {
+            speeds: weighted_speeds,
+        }
     }
 
     pub fn speeds(&self) -> &[Rational] {
         &self.speeds
     }
 }
+
+/// Optimizes machine speeds as an alternative to balancing all [`Product`]s exactly.
+///
+/// Each [`Setup`] runs somewhere between stopped and full speed. Unless overridden by
+/// [`Self::constraints`], [`ProcessingChain::is_balanced`] [`Product`]s must still net to zero.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinearProgram {
+    #[serde(default)]
+    pub objective: Objective,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<ProductConstraint>,
+}
+
+impl LinearProgram {
+    pub fn constraint(&self, product: &Product) -> Option<NetRate> {
+        self.constraints
+            .iter()
+            .find(|constraint| constraint.product == *product)
+            .map(|constraint| constraint.net_rate.clone())
+    }
+
+    /// Whether stopping all [`Setup`]s is already optimal, because [`Objective::MinimizeEu`] has
+    /// no [`NetRate::Bounded`] constraint that requires any [`Product`] to be produced or
+    /// consumed.
+    pub fn stops_all_setups(&self) -> bool {
+        self.objective == Objective::MinimizeEu
+            && !self.constraints.iter().any(|constraint| {
+                matches!(&constraint.net_rate, NetRate::Bounded { min, max } if *min > 0 || *max < 0)
+            })
+    }
+
+    /// Overrides the [`NetRate`] of a `product` or resets it to the default if [`None`].
+    pub fn set_constraint(&mut self, product: &Product, net_rate: Option<NetRate>) {
+        self.constraints
+            .retain(|constraint| constraint.product != *product);
+        if let Some(net_rate) = net_rate {
+            self.constraints.push(ProductConstraint {
+                product: product.clone(),
+                net_rate,
+            });
+        }
+    }
+
+    fn replace_product(&mut self, old: &Product, new: &Product) {
+        if let Objective::MaximizeOutput(product) = &mut self.objective {
+            if product == old {
+                *product = new.clone();
+            }
+        }
+
+        if self.constraint(new).is_some() {
+            // keep the existing constraint of the product that is merged into
+            self.constraints
+                .retain(|constraint| constraint.product != *old);
+        } else {
+            for constraint in &mut self.constraints {
+                if constraint.product == *old {
+                    constraint.product = new.clone();
+                }
+            }
+        }
+    }
+
+    /// Any [`Setup`]s with a [`MachinePowerError`] are stopped.
+    fn solve(&self, processing_chain: &ProcessingChain) -> Result<Vec<Rational>, SimplexError> {
+        let setups_len = processing_chain.setups.len();
+        debug!("Optimizing speeds for {setups_len} setups.");
+
+        let setup_products_per_sec = processing_chain
+            .setups
+            .iter()
+            .map(|setup| setup.products_per_sec().ok())
+            .collect_vec();
+
+        let product_row = |product: &Product| {
+            setup_products_per_sec
+                .iter()
+                .map(|products_per_sec| {
+                    products_per_sec
+                        .as_ref()
+                        .and_then(|products_per_sec| products_per_sec.get(product))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect_vec()
+        };
+
+        let mut constraints = setup_products_per_sec
+            .iter()
+            .enumerate()
+            .map(|(index, products_per_sec)| {
+                let mut coefficients = vec![Rational::ZERO; setups_len];
+                coefficients[index] = Rational::ONE;
+                Constraint {
+                    coefficients,
+                    relation: Relation::LessEqual,
+                    rhs: if products_per_sec.is_some() {
+                        Rational::ONE
+                    } else {
+                        Rational::ZERO
+                    },
+                }
+            })
+            .collect_vec();
+
+        for product in processing_chain.products() {
+            let net_rate = self.constraint(product).or_else(|| {
+                processing_chain
+                    .is_balanced(product)
+                    .then_some(NetRate::Zero)
+            });
+            if let Some(net_rate) = net_rate {
+                constraints.extend(net_rate.constraints(product_row(product)));
+            }
+        }
+
+        if let Some(eu_row) = Speeds::eu_row(processing_chain) {
+            constraints.extend(NetRate::Zero.constraints(eu_row));
+        }
+
+        let objective = match &self.objective {
+            Objective::MaximizeSpeeds => vec![Rational::ONE; setups_len],
+            Objective::MaximizeOutput(product) => product_row(product),
+            Objective::MinimizeEu => processing_chain
+                .setups
+                .iter()
+                .map(|setup| setup.eu_per_tick().map(Rational::from).unwrap_or_default())
+                .collect(),
+        };
+
+        maximize(&objective, &constraints)
+    }
+}
+
+/// What a [`LinearProgram`] optimizes for.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// Runs all [`Setup`]s as fast as possible.
+    #[default]
+    MaximizeSpeeds,
+    /// Maximizes the net output of a single [`Product`].
+    MaximizeOutput(Product),
+    /// Minimizes the net EU consumption, or maximizes the net EU production.
+    ///
+    /// Without a [`NetRate::Bounded`] constraint that requires some output, this simply stops
+    /// all [`Setup`]s.
+    MinimizeEu,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProductConstraint {
+    pub product: Product,
+    pub net_rate: NetRate,
+}
+
+/// Constrains the net rate at which a [`Product`] is produced by the entire chain per second.
+///
+/// A negative rate means that the [`Product`] is consumed.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetRate {
+    /// The [`Product`] must be balanced exactly.
+    Zero,
+    /// More of the [`Product`] may be produced than consumed.
+    Surplus,
+    /// More of the [`Product`] may be consumed than produced.
+    Deficit,
+    /// The net rate must stay within `min` and `max`.
+    Bounded {
+        #[serde(with = "rational")]
+        min: Rational,
+        #[serde(with = "rational")]
+        max: Rational,
+    },
+}
+
+impl NetRate {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Zero => "Zero",
+            Self::Surplus => "Surplus",
+            Self::Deficit => "Deficit",
+            Self::Bounded { .. } => "Bounded",
+        }
+    }
+
+    fn constraints(&self, coefficients: Vec<Rational>) -> Vec<Constraint> {
+        let constraint = |coefficients, relation, rhs| Constraint {
+            coefficients,
+            relation,
+            rhs,
+        };
+        match self {
+            Self::Zero => vec![constraint(coefficients, Relation::Equal, Rational::ZERO)],
+            Self::Surplus => vec![constraint(
+                coefficients,
+                Relation::GreaterEqual,
+                Rational::ZERO,
+            )],
+            Self::Deficit => vec![constraint(
+                coefficients,
+                Relation::LessEqual,
+                Rational::ZERO,
+            )],
+            Self::Bounded { min, max } => vec![
+                constraint(coefficients.clone(), Relation::GreaterEqual, min.clone()),
+                constraint(coefficients, Relation::LessEqual, max.clone()),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        machine::{ClockedMachine, ClockedMachines, Voltage},
+        test_util::{processing_chain, setup},
+    };
+
+    #[test]
+    fn unrelated_components_run_at_full_speed() {
+        let processing_chain = processing_chain(vec![
+            setup(None, ("Plate", 1)),
+            setup(Some(("Plate", 2)), ("Gear", 1)),
+            setup(None, ("Rod", 3)),
+            setup(Some(("Rod", 1)), ("Bolt", 1)),
+        ]);
+
+        let components = &processing_chain.speeds().diagnostics().components;
+        assert_eq!(components, &[vec![0, 1], vec![2, 3]]);
+        let speeds = processing_chain.weighted_speeds().speeds();
+        for component in components {
+            assert_eq!(
+                component.iter().map(|&index| &speeds[index]).max(),
+                Some(&Rational::ONE)
+            );
+        }
+        assert_eq!(
+            speeds,
+            [
+                Rational::ONE,
+                Rational::from_unsigneds(1u8, 2),
+                Rational::from_unsigneds(1u8, 3),
+                Rational::ONE,
+            ]
+        );
+    }
+
+    #[test]
+    fn weight_shares_follow_weighted_speeds() {
+        let mut processing_chain = processing_chain(vec![
+            setup(None, ("Plate", 1)),
+            setup(None, ("Plate", 2)),
+            setup(Some(("Plate", 1)), ("Gear", 1)),
+        ]);
+        processing_chain.set_weight(1, Weight(3));
+
+        let plate = Product {
+            name: "Plate".into(),
+            form: Default::default(),
+        };
+        let speeds = processing_chain.weighted_speeds().speeds().to_vec();
+        let produced = [speeds[0].clone(), Rational::from(2) * &speeds[1]];
+        let total = &produced[0] + &produced[1];
+        for (index, produced) in produced.into_iter().enumerate() {
+            assert_eq!(
+                processing_chain.weight_shares(index),
+                [(&plate, produced / &total)]
+            );
+        }
+    }
+
+    #[test]
+    fn saved_chains_reopen_unchanged() {
+        let mut processing_chain = processing_chain(vec![
+            setup(None, ("Plate", 1)),
+            setup(Some(("Plate", 2)), ("Gear", 1)),
+        ]);
+        processing_chain.set_weight(1, Weight(2));
+        processing_chain.set_balance_eu(true);
+        let gear_setup = &mut processing_chain.setups_mut()[1];
+        gear_setup.recipe.eu_per_tick = -30;
+        gear_setup.machines = Machines::Power(ClockedMachines {
+            machines: [(
+                ClockedMachine::with_underclocking(Voltage::High, Voltage::Medium)
+                    .with_parallels(NonZeroU64::new(4).unwrap()),
+                NonZeroU64::new(2).unwrap(),
+            )]
+            .into(),
+            ..Default::default()
+        });
+
+        let json = processing_chain.to_json();
+        let reopened = serde_json::from_str::<ProcessingChain>(&json).unwrap();
+        assert_eq!(reopened, processing_chain);
+        assert_eq!(reopened.to_json(), json);
+    }
+}
@@ -1,9 +1,17 @@
-use std::{collections::BTreeMap, num::NonZeroU64};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroU64,
+    sync::LazyLock,
+};
 
 use malachite::{Integer, Rational};
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use thiserror::Error;
 
-use super::machine::Voltage;
+use super::{
+    machine::{Overclocking, Voltage},
+    serde_util::{is_false, is_one, is_zero, one},
+};
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -12,9 +20,28 @@ pub struct Recipe {
     pub ticks: NonZeroU64,
     #[serde(default)]
     pub eu_per_tick: i64,
+    /// How many amps [`Self::eu_per_tick`] is split across, e.g. `2` for a 2A recipe.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub amperage: NonZeroU64,
+    /// Basis points that are added to every chanced output per overclocking tier.
+    ///
+    /// Only applies to outputs that are not already guaranteed and is capped at 100%.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub chance_boost: u16,
+    #[serde(default, skip_serializing_if = "Overclocking::is_normal")]
+    pub overclocking: Overclocking,
+    /// Whether overclocking beyond a single tick processes multiple recipes at once instead.
+    ///
+    /// Without this, overclocking simply stops once the duration reaches a single tick.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub subtick_parallels: bool,
+    /// The minimum heat in Kelvin required by machines with [`Coil`](super::machine::Coil)s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heat: Option<NonZeroU64>,
     #[serde(default)]
     pub catalysts: Vec<Product>,
-    #[serde(default)]
+    /// Always guaranteed, since only outputs are rolled with a [`Chance`].
+    #[serde(default, deserialize_with = "deserialize_consumed")]
     pub consumed: Vec<ProductCount>,
     #[serde(default)]
     pub produced: Vec<ProductCount>,
@@ -26,6 +53,11 @@ impl Recipe {
             machine,
             ticks: NonZeroU64::MIN,
             eu_per_tick: Default::default(),
+            amperage: NonZeroU64::MIN,
+            chance_boost: Default::default(),
+            overclocking: Default::default(),
+            subtick_parallels: Default::default(),
+            heat: Default::default(),
             catalysts: Default::default(),
             consumed: Default::default(),
             produced: Default::default(),
@@ -36,12 +68,14 @@ impl Recipe {
         Integer::from(self.ticks.get()) * Integer::from(self.eu_per_tick)
     }
 
-    /// Returns the minimum required [`Voltage`] based on [`Self::eu_per_tick`].
+    /// Returns the minimum required [`Voltage`] based on [`Self::eu_per_tick`] and
+    /// [`Self::amperage`].
     ///
     /// Returns [`None`] if the recipe neither consumes nor produces power.
     pub fn voltage(&self) -> Option<Voltage> {
         Some(Voltage::from_signed_eu_per_tick(
             self.eu_per_tick.try_into().ok()?,
+            self.amperage,
         ))
     }
 
@@ -59,16 +93,28 @@ impl Recipe {
         consumed.chain(produced)
     }
 
-    pub fn product_counts(&self) -> BTreeMap<&Product, Integer> {
+    /// Returns the expected number of each [`Product`] per processing cycle.
+    ///
+    /// Consumed products are negative. [`ProductCount::chance`] is taken into account, so the
+    /// counts are not necessarily whole numbers.
+    pub fn product_counts(&self) -> BTreeMap<&Product, Rational> {
+        self.boosted_product_counts(0)
+    }
+
+    /// Like [`Self::product_counts`] but with [`Self::chance_boost`] applied `tiers` times to
+    /// produced products.
+    pub fn boosted_product_counts(&self, tiers: u8) -> BTreeMap<&Product, Rational> {
         let consumed = self
             .consumed
             .iter()
-            .map(|ProductCount { product, count }| (product, -Integer::from(count.get())));
+            .map(|product_count| (&product_count.product, -product_count.expected_count()));
 
-        let produced = self
-            .produced
-            .iter()
-            .map(|ProductCount { product, count }| (product, Integer::from(count.get())));
+        let produced = self.produced.iter().map(|product_count| {
+            (
+                &product_count.product,
+                product_count.boosted_expected_count(self.chance_boost, tiers),
+            )
+        });
 
         consumed
             .chain(produced)
@@ -79,10 +125,18 @@ impl Recipe {
     }
 
     pub fn products_per_sec(&self) -> impl Iterator<Item = (&Product, Rational)> {
+        self.boosted_products_per_sec(0)
+    }
+
+    /// Like [`Self::products_per_sec`] but with [`Self::chance_boost`] applied `tiers` times.
+    pub fn boosted_products_per_sec(
+        &self,
+        tiers: u8,
+    ) -> impl Iterator<Item = (&Product, Rational)> {
         let seconds = self.seconds();
-        self.product_counts()
+        self.boosted_product_counts(tiers)
             .into_iter()
-            .map(move |(product, count)| (product, Rational::from(count) / &seconds))
+            .map(move |(product, count)| (product, count / &seconds))
     }
 
     pub fn produces(&self, product: &Product) -> bool {
@@ -101,6 +155,9 @@ impl Recipe {
         Rational::const_from_unsigneds(self.ticks.get(), 20)
     }
 
+    /// Replaces all occurrences of `old` with `new`.
+    ///
+    /// If `new` was already part of the recipe, its counts are merged.
     pub fn replace_product(&mut self, old: &Product, new: &Product) {
         for product in self
             .consumed
@@ -112,7 +169,50 @@ impl Recipe {
         {
             *product = new.clone();
         }
+
+        merge_product_counts(&mut self.consumed, new);
+        merge_product_counts(&mut self.produced, new);
+
+        let mut seen = BTreeSet::new();
+        self.catalysts
+            .retain(|product| product != new || seen.insert(product.clone()));
+    }
+}
+
+/// Sums up the counts of all entries of the given `product` that have the same [`Chance`].
+fn merge_product_counts(product_counts: &mut Vec<ProductCount>, product: &Product) {
+    let mut merged = Vec::<ProductCount>::with_capacity(product_counts.len());
+    for product_count in product_counts.drain(..) {
+        let existing = merged.iter_mut().find(|existing| {
+            existing.product == *product
+                && existing.product == product_count.product
+                && existing.chance == product_count.chance
+        });
+        match existing {
+            Some(existing) => {
+                existing.count = existing.count.saturating_add(product_count.count.get())
+            }
+            None => merged.push(product_count),
+        }
+    }
+    *product_counts = merged;
+}
+
+/// Rejects a [`ProductCount::chance`] on consumed products.
+fn deserialize_consumed<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<ProductCount>, D::Error> {
+    let consumed = Vec::<ProductCount>::deserialize(deserializer)?;
+    if let Some(product_count) = consumed
+        .iter()
+        .find(|product_count| !product_count.chance.is_guaranteed())
+    {
+        return Err(D::Error::custom(format!(
+            "consumed product \"{}\" cannot have a chance",
+            product_count.product.name
+        )));
     }
+    Ok(consumed)
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -121,10 +221,90 @@ pub struct Machine {
     pub name: String,
 }
 
+/// Serialized as just its name for [`ProductForm::Item`], which keeps the common case short.
+///
+/// Products are identified by both their name and their form, so e.g. "Oxygen" cells and
+/// "Oxygen" gas are different products that never balance against each other, just like in the
+/// game.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(transparent)]
+#[serde(from = "ProductRepr", into = "ProductRepr")]
 pub struct Product {
     pub name: String,
+    /// Part of the identity of the product; see above.
+    pub form: ProductForm,
+}
+
+impl Product {
+    /// The steam consumed by [`Machines::Steam`](super::machine::Machines::Steam).
+    pub fn steam() -> &'static Self {
+        static STEAM: LazyLock<Product> = LazyLock::new(|| Product {
+            name: "Steam".into(),
+            form: ProductForm::Fluid,
+        });
+        &STEAM
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ProductRepr {
+    Item(String),
+    Product { name: String, form: ProductForm },
+}
+
+impl From<ProductRepr> for Product {
+    fn from(value: ProductRepr) -> Self {
+        match value {
+            ProductRepr::Item(name) => Self {
+                name,
+                form: ProductForm::Item,
+            },
+            ProductRepr::Product { name, form } => Self { name, form },
+        }
+    }
+}
+
+impl From<Product> for ProductRepr {
+    fn from(Product { name, form }: Product) -> Self {
+        match form {
+            ProductForm::Item => Self::Item(name),
+            _ => Self::Product { name, form },
+        }
+    }
+}
+
+/// Determines the unit in which amounts of a [`Product`] are measured.
+#[derive(
+    Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductForm {
+    /// Counted in items, which stack up to 64.
+    #[default]
+    Item,
+    /// Measured in liters, with 1000 L making up a bucket.
+    Fluid,
+    /// Anything that is neither an item nor a fluid, e.g. EU or research points.
+    Virtual,
+}
+
+impl ProductForm {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Item => "Item",
+            Self::Fluid => "Fluid",
+            Self::Virtual => "Virtual",
+        }
+    }
+
+    /// The suffix for amounts of this form, including a leading space if not empty.
+    pub const fn unit(self) -> &'static str {
+        match self {
+            Self::Item => " items",
+            Self::Fluid => " L",
+            Self::Virtual => "",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -132,4 +312,100 @@ pub struct Product {
 pub struct ProductCount {
     pub product: Product,
     pub count: NonZeroU64,
+    /// The chance of [`Self::count`] being produced per processing cycle.
+    ///
+    /// Only [`Recipe::produced`] entries may have a chance.
+    #[serde(default, skip_serializing_if = "Chance::is_guaranteed")]
+    pub chance: Chance,
+}
+
+impl ProductCount {
+    pub fn new(product: Product) -> Self {
+        Self {
+            product,
+            count: NonZeroU64::MIN,
+            chance: Default::default(),
+        }
+    }
+
+    /// The average count per processing cycle based on [`Self::chance`].
+    pub fn expected_count(&self) -> Rational {
+        self.boosted_expected_count(0, 0)
+    }
+
+    /// Like [`Self::expected_count`] but with the [`Chance`] boosted by `boost` basis points
+    /// per tier.
+    pub fn boosted_expected_count(&self, boost: u16, tiers: u8) -> Rational {
+        Rational::from(self.count.get()) * self.chance.boosted(boost, tiers).probability()
+    }
+}
+
+/// A chance in basis points like GregTech uses them, i.e. `10000` is 100%.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Chance(u16);
+
+impl Chance {
+    pub const MIN: Self = Self(1);
+    pub const GUARANTEED: Self = Self(Self::BASIS_POINTS);
+
+    const BASIS_POINTS: u16 = 10000;
+
+    /// Returns [`None`] if `basis_points` is zero or greater than `10000`.
+    pub const fn new(basis_points: u16) -> Option<Self> {
+        if basis_points != 0 && basis_points <= Self::BASIS_POINTS {
+            Some(Self(basis_points))
+        } else {
+            None
+        }
+    }
+
+    pub const fn basis_points(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_guaranteed(&self) -> bool {
+        *self == Self::GUARANTEED
+    }
+
+    /// Adds `boost` basis points per tier, capped at 100%.
+    ///
+    /// Guaranteed chances are never boosted.
+    pub fn boosted(self, boost: u16, tiers: u8) -> Self {
+        Self(
+            (u32::from(self.0) + u32::from(boost) * u32::from(tiers))
+                .min(u32::from(Self::BASIS_POINTS))
+                .try_into()
+                .expect("should be capped at basis points"),
+        )
+    }
+
+    /// The chance as an exact fraction in the range `(0, 1]`.
+    pub fn probability(self) -> Rational {
+        Rational::from_unsigneds(self.0, Self::BASIS_POINTS)
+    }
+}
+
+impl Default for Chance {
+    fn default() -> Self {
+        Self::GUARANTEED
+    }
+}
+
+impl From<Chance> for u16 {
+    fn from(chance: Chance) -> Self {
+        chance.0
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid chance; should be between 1 and 10000 basis points")]
+pub struct ChanceFromU16Error;
+
+impl TryFrom<u16> for Chance {
+    type Error = ChanceFromU16Error;
+
+    fn try_from(basis_points: u16) -> Result<Self, Self::Error> {
+        Self::new(basis_points).ok_or(ChanceFromU16Error)
+    }
 }
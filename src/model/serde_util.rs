@@ -0,0 +1,33 @@
+use std::num::NonZeroU64;
+
+pub(super) fn is_zero(value: &u16) -> bool {
+    *value == 0
+}
+
+pub(super) fn is_false(value: &bool) -> bool {
+    !value
+}
+
+pub(super) fn one() -> NonZeroU64 {
+    NonZeroU64::MIN
+}
+
+pub(super) fn is_one(value: &NonZeroU64) -> bool {
+    *value == NonZeroU64::MIN
+}
+
+/// Serializes [`Rational`]s as strings like `"3/2"`.
+pub mod rational {
+    use malachite::Rational;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Rational, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rational, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|()| D::Error::custom("invalid rational; should be e.g. \"3/2\""))
+    }
+}
@@ -0,0 +1,36 @@
+use std::num::NonZeroU64;
+
+use super::{
+    machine::Machines,
+    processing_chain::{ProcessingChain, Setup},
+    recipe::{Machine, Product, ProductCount, Recipe},
+};
+
+/// A single eco machine turning the `consumed` product into the `produced` one every second.
+pub fn setup(consumed: Option<(&str, u64)>, produced: (&str, u64)) -> Setup {
+    let product_count = |(name, count): (&str, u64)| ProductCount {
+        count: NonZeroU64::new(count).unwrap(),
+        ..ProductCount::new(Product {
+            name: name.into(),
+            form: Default::default(),
+        })
+    };
+    Setup {
+        recipe: Recipe {
+            ticks: NonZeroU64::new(20).unwrap(),
+            consumed: consumed.into_iter().map(product_count).collect(),
+            produced: vec![product_count(produced)],
+            ..Recipe::new(Machine {
+                name: "Assembler".into(),
+            })
+        },
+        machines: Machines::Eco(1),
+        weight: Default::default(),
+    }
+}
+
+pub fn processing_chain(setups: Vec<Setup>) -> ProcessingChain {
+    let mut processing_chain = ProcessingChain::default();
+    *processing_chain.setups_mut() = setups;
+    processing_chain
+}
@@ -1,3 +1,3 @@
 pub mod app;
 mod processing_chain_table;
-// mod tabs;
+mod tabs;
@@ -1,58 +1,237 @@
-use eframe::{App, CreationContext, Frame, Storage};
-use egui::{global_theme_preference_switch, menu, Button, CentralPanel, Context, TopBottomPanel};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::read_to_string;
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+use eframe::{get_value, set_value, App, CreationContext, Frame, Storage};
+use egui::{global_theme_preference_switch, menu, Button, Context, RichText, TopBottomPanel};
+use egui_dock::{DockArea, DockState};
 use log::info;
+#[cfg(target_arch = "wasm32")]
+use rfd::AsyncFileDialog;
+#[cfg(not(target_arch = "wasm32"))]
+use rfd::FileDialog;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::tabs::{processing_chain_file_dialog, Tab, Tabs};
+use crate::model::fuel::FuelTable;
 
-use super::processing_chain_table::{ProcessingChainTable, ViewMode};
+const CONFIG_KEY: &str = "config";
+const TABS_KEY: &str = "tabs";
+const DOCK_STATE_KEY: &str = "dock_state";
 
 #[derive(Clone, Debug)]
 pub struct GregCalc {
-    // config: Config,
-    // tabs: Tabs,
-    // dock_state: DockState<Tab>,
-    processing_chain_state: ProcessingChainTable,
-    processing_chain_view_mode: ViewMode,
+    config: Config,
+    tabs: Tabs,
+    dock_state: DockState<Tab>,
     notifications: Vec<Notification>,
+    #[cfg(target_arch = "wasm32")]
+    picked_files: PickedFiles,
+}
+
+/// Files picked in the browser, which are read asynchronously.
+#[cfg(target_arch = "wasm32")]
+type PickedFiles = Rc<RefCell<Vec<PickedFile>>>;
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Debug)]
+enum PickedFile {
+    ProcessingChain { file_name: String, bytes: Vec<u8> },
+    FuelTable { file_name: String, bytes: Vec<u8> },
+}
+
+/// Settings that apply to all [`Tab`]s.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// The [`FuelTable`] opened by the user, if it should replace the bundled `fuels.json`.
+    fuel_table: Option<FuelTable>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-enum Notification {
+pub(super) enum Notification {
     Error(String),
 }
 
 impl GregCalc {
-    pub fn new(_creation_context: &CreationContext) -> Self {
-        // let mut config = Default::default();
-        // let mut tabs = Default::default();
-        // let dock_state;
-        // if let Some(storage) = creation_context.storage {
-        //     config = get_value(storage, CONFIG_KEY).unwrap_or_default();
-        //     dock_state = get_value(storage, DOCK_STATE_KEY)
-        //         .unwrap_or_else(|| DockState::new(Self::default_tabs()));
-        //     tabs = get_value(storage, TABS_KEY).unwrap_or_default();
-        // } else {
-        //     dock_state = DockState::new(Self::default_tabs());
-        // }
+    pub fn new(creation_context: &CreationContext) -> Self {
+        let mut notifications = Vec::new();
+        let mut config = Config::default();
+        let restored = creation_context.storage.and_then(|storage| {
+            config = restore(storage, CONFIG_KEY, &mut notifications).unwrap_or_default();
+            Some((
+                restore::<Tabs>(storage, TABS_KEY, &mut notifications)?,
+                restore::<DockState<Tab>>(storage, DOCK_STATE_KEY, &mut notifications)?,
+            ))
+        });
+
+        let fuel_table = config
+            .fuel_table
+            .clone()
+            .unwrap_or_else(|| serde_json::from_str(include_str!("../../fuels.json")).unwrap());
+
+        let (tabs, dock_state) = match restored {
+            Some((mut tabs, mut dock_state)) => {
+                info!("Restoring {} tabs", dock_state.iter_all_tabs().count());
+                tabs.set_fuel_table(fuel_table);
+                tabs.retain_open(&mut dock_state);
+                (tabs, dock_state)
+            }
+            None => {
+                let mut tabs = Tabs::new(fuel_table);
+                let example = tabs.insert_new_processing_chain(
+                    serde_json::from_str(include_str!("../../recipes.json")).unwrap(),
+                );
+                (tabs, DockState::new(vec![example]))
+            }
+        };
 
         Self {
-            // config,
-            // tabs,
-            // dock_state,
-            processing_chain_state: ProcessingChainTable::new(
-                serde_json::from_str(include_str!("../../recipes.json")).unwrap(),
-            ),
-            processing_chain_view_mode: ViewMode::Recipe,
-            notifications: Default::default(),
+            config,
+            tabs,
+            dock_state,
+            notifications,
+            #[cfg(target_arch = "wasm32")]
+            picked_files: Default::default(),
         }
     }
 
-    // fn focus_or_push_tab(&mut self, tab: Tab) {
-    //     if let Some((surface_index, node_index, _)) = self.dock_state.find_tab(&tab) {
-    //         self.dock_state
-    //             .set_focused_node_and_surface((surface_index, node_index));
-    //     } else {
-    //         self.dock_state.push_to_first_leaf(tab);
-    //     }
-    // }
+    fn focus_or_push_tab(&mut self, tab: Tab) {
+        if let Some(tab_path) = self.dock_state.find_tab(&tab) {
+            let (surface_index, node_index, _) = tab_path;
+            self.dock_state.set_active_tab(tab_path);
+            self.dock_state
+                .set_focused_node_and_surface((surface_index, node_index));
+        } else {
+            self.dock_state.push_to_focused_leaf(tab);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_processing_chains(&mut self) {
+        for path in processing_chain_file_dialog()
+            .pick_files()
+            .into_iter()
+            .flatten()
+        {
+            info!("Opening {path:?}");
+            match self.tabs.load_processing_chain_tab(path) {
+                Ok(tab) => self.focus_or_push_tab(tab),
+                Err(notification) => self.notifications.push(notification),
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn open_processing_chains(&mut self, ctx: &Context) {
+        let picked_files = self.picked_files.clone();
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            for file in processing_chain_file_dialog()
+                .pick_files()
+                .await
+                .into_iter()
+                .flatten()
+            {
+                let bytes = file.read().await;
+                picked_files.borrow_mut().push(PickedFile::ProcessingChain {
+                    file_name: file.file_name(),
+                    bytes,
+                });
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_fuel_table(&mut self) {
+        let Some(path) = fuel_table_file_dialog().pick_file() else {
+            return;
+        };
+        info!("Opening fuel table {path:?}");
+        let fuel_table = read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_str(&json)?));
+        self.set_fuel_table(&path.display().to_string(), fuel_table);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn open_fuel_table(&mut self, ctx: &Context) {
+        let picked_files = self.picked_files.clone();
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Some(file) = fuel_table_file_dialog().pick_file().await {
+                let bytes = file.read().await;
+                picked_files.borrow_mut().push(PickedFile::FuelTable {
+                    file_name: file.file_name(),
+                    bytes,
+                });
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn set_fuel_table(&mut self, name: &str, fuel_table: anyhow::Result<FuelTable>) {
+        match fuel_table {
+            Ok(fuel_table) => {
+                self.config.fuel_table = Some(fuel_table.clone());
+                self.tabs.set_fuel_table(fuel_table);
+            }
+            Err(error) => self
+                .notifications
+                .push(Notification::Error(format!("{name}: {error}"))),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load_picked_files(&mut self) {
+        let picked_files = self.picked_files.take();
+        for picked_file in picked_files {
+            match picked_file {
+                PickedFile::ProcessingChain { file_name, bytes } => {
+                    info!("Opening {file_name:?}");
+                    match self.tabs.load_processing_chain_bytes(file_name, &bytes) {
+                        Ok(tab) => self.focus_or_push_tab(tab),
+                        Err(notification) => self.notifications.push(notification),
+                    }
+                }
+                PickedFile::FuelTable { file_name, bytes } => {
+                    info!("Opening fuel table {file_name:?}");
+                    let fuel_table = serde_json::from_slice(&bytes).map_err(anyhow::Error::from);
+                    self.set_fuel_table(&file_name, fuel_table);
+                }
+            }
+        }
+    }
+
+    /// Opens files dropped onto the window, which have a path natively and bytes in the browser.
+    fn open_dropped_files(&mut self, ctx: &Context) {
+        let dropped_files = ctx.input(|input_state| input_state.raw.dropped_files.clone());
+        for file in dropped_files {
+            info!("Opening dropped {file:?}");
+            #[cfg(not(target_arch = "wasm32"))]
+            let tab = match file.path {
+                Some(path) => self.tabs.load_processing_chain_tab(path),
+                None => Err(Notification::Error(format!(
+                    "{}: dropped file has no path",
+                    file.name
+                ))),
+            };
+            #[cfg(target_arch = "wasm32")]
+            let tab = match &file.bytes {
+                Some(bytes) => self.tabs.load_processing_chain_bytes(file.name, bytes),
+                None => Err(Notification::Error(format!(
+                    "{}: dropped file has no content",
+                    file.name
+                ))),
+            };
+            match tab {
+                Ok(tab) => self.focus_or_push_tab(tab),
+                Err(notification) => self.notifications.push(notification),
+            }
+        }
+    }
 
     fn show_menu_bar(&mut self, ctx: &Context) {
         TopBottomPanel::top("menu").show(ctx, |ui| {
@@ -62,57 +241,52 @@ impl GregCalc {
                 ui.menu_button("File", |ui| {
                     if ui.button("New Processing Chain").clicked() {
                         ui.close_menu();
-                        // self.focus_or_push_tab(Tab::ProcessingChain(ProcessingChainTab::new()));
+                        let tab = self.tabs.insert_new_processing_chain(Default::default());
+                        self.dock_state.push_to_focused_leaf(tab);
                     }
                     if ui.button("Open Processing Chain...").clicked() {
                         ui.close_menu();
-                        // TODO use processing_chain_file_dialog() again
-                        // for file in AsyncFileDialog::new()
-                        //     .add_filter("Processing Chain", &["json"])
-                        //     .pick_files()
-                        //     .await
-                        //     .into_iter()
-                        // {
-                        //     info!("{file:?}");
-                        //     match self.tabs.load_processing_chain_tab(path) {
-                        //         Ok(tab) => self.focus_or_push_tab(tab),
-                        //         Err(notification) => self.notifications.push(notification),
-                        //     }
-                        // }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        self.open_processing_chains();
+                        #[cfg(target_arch = "wasm32")]
+                        self.open_processing_chains(ui.ctx());
                     }
 
-                    let tab = None::<()>;
-                    // let tab =
-                    //     self.dock_state
-                    //         .find_active_focused()
-                    //         .and_then(|(_, tab)| match &*tab {
-                    //             Tab::Config => None,
-                    //             Tab::ProcessingChain(processing_chain_tab) => {
-                    //                 Some(processing_chain_tab)
-                    //             }
-                    //         });
+                    let tab = self.dock_state.find_active_focused().map(
+                        |(_, Tab::ProcessingChain(processing_chain_tab))| processing_chain_tab,
+                    );
 
                     if ui.add_enabled(tab.is_some(), Button::new("Save")).clicked() {
                         ui.close_menu();
-                        // let notification = self
-                        //     .tabs
-                        //     .save_processing_chain_tab(tab.expect("tab should exist"));
-                        // self.notifications.extend(notification);
-                    }
-
-                    if ui
+                        let tab = tab.expect("tab should exist");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        let notification = self.tabs.save_processing_chain_tab(tab);
+                        #[cfg(target_arch = "wasm32")]
+                        let notification = self.tabs.download_processing_chain_tab(tab);
+                        self.notifications.extend(notification);
+                    } else if ui
                         .add_enabled(tab.is_some(), Button::new("Save As..."))
                         .clicked()
                     {
                         ui.close_menu();
-                        // let notification = self
-                        //     .tabs
-                        //     .save_processing_chain_tab_as(tab.expect("tab should exist"));
-                        // self.notifications.extend(notification);
+                        let tab = tab.expect("tab should exist");
+                        #[cfg(not(target_arch = "wasm32"))]
+                        let notification = self.tabs.save_processing_chain_tab_as(tab);
+                        // the browser decides where downloads end up
+                        #[cfg(target_arch = "wasm32")]
+                        let notification = self.tabs.download_processing_chain_tab(tab);
+                        self.notifications.extend(notification);
                     }
 
                     ui.separator();
 
+                    if ui.button("Open Fuel Table...").clicked() {
+                        ui.close_menu();
+                        #[cfg(not(target_arch = "wasm32"))]
+                        self.open_fuel_table();
+                        #[cfg(target_arch = "wasm32")]
+                        self.open_fuel_table(ui.ctx());
+                    }
                     if ui.button("Config").clicked() {
                         ui.close_menu();
                         // self.focus_or_push_tab(Tab::Config);
@@ -132,42 +306,88 @@ impl GregCalc {
         });
     }
 
-    // fn show_dock_area(&mut self, ctx: &Context) {
-    //     DockArea::new(&mut self.dock_state)
-    //         .show_add_buttons(true)
-    //         .show_window_close_buttons(false)
-    //         .show_tab_name_on_hover(true)
-    //         .show(ctx, &mut self.tabs);
-    // }
-
-    // fn default_tabs() -> Vec<Tab> {
-    //     vec![Tab::ProcessingChain(ProcessingChainTab::new())]
-    // }
+    fn show_notifications(&mut self, ctx: &Context) {
+        if self.notifications.is_empty() {
+            return;
+        }
+
+        TopBottomPanel::bottom("notifications").show(ctx, |ui| {
+            self.notifications.retain(|notification| {
+                ui.horizontal(|ui| {
+                    let dismissed = ui.button("❌").on_hover_text("Dismiss").clicked();
+                    match notification {
+                        Notification::Error(error) => {
+                            ui.label(
+                                RichText::new(format!("⚠ {error}"))
+                                    .color(ui.visuals().error_fg_color),
+                            );
+                        }
+                    }
+                    !dismissed
+                })
+                .inner
+            });
+        });
+    }
+
+    fn show_dock_area(&mut self, ctx: &Context) {
+        DockArea::new(&mut self.dock_state)
+            .show_add_buttons(true)
+            .show_window_close_buttons(false)
+            .show_tab_name_on_hover(true)
+            .show(ctx, &mut self.tabs);
+
+        self.notifications.extend(self.tabs.take_notifications());
+
+        for (surface, node) in self.tabs.take_new_tabs() {
+            let tab = self.tabs.insert_new_processing_chain(Default::default());
+            self.dock_state[surface][node].append_tab(tab);
+        }
+
+        self.tabs.show_unsaved_changes_dialog(ctx);
+    }
 }
 
 impl App for GregCalc {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        ctx.input(|input_state| {
-            for file in &input_state.raw.dropped_files {
-                // TODO: Open file
-                info!("Dropped: {file:#?}");
-            }
-        });
+        self.open_dropped_files(ctx);
+        #[cfg(target_arch = "wasm32")]
+        self.load_picked_files();
 
         self.show_menu_bar(ctx);
-        // self.show_dock_area(ctx);
+        self.show_notifications(ctx);
+        self.show_dock_area(ctx);
+    }
 
-        CentralPanel::default().show(ctx, |ui| {
-            ui.add(&mut self.processing_chain_view_mode);
-            ui.separator();
-            self.processing_chain_state
-                .show(self.processing_chain_view_mode, ui);
-        });
+    fn save(&mut self, storage: &mut dyn Storage) {
+        set_value(storage, CONFIG_KEY, &self.config);
+        set_value(storage, DOCK_STATE_KEY, &self.dock_state);
+        set_value(storage, TABS_KEY, &self.tabs);
     }
+}
 
-    fn save(&mut self, _storage: &mut dyn Storage) {
-        // set_value(storage, CONFIG_KEY, &self.config);
-        // set_value(storage, DOCK_STATE_KEY, &self.dock_state);
-        // set_value(storage, TABS_KEY, &self.tabs);
+/// Like [`get_value`], but raises a [`Notification`] if a value exists that cannot be restored,
+/// e.g. because it was saved by an incompatible version.
+fn restore<T: DeserializeOwned>(
+    storage: &dyn Storage,
+    key: &str,
+    notifications: &mut Vec<Notification>,
+) -> Option<T> {
+    let value = get_value(storage, key);
+    if value.is_none() && storage.get_string(key).is_some() {
+        notifications.push(Notification::Error(format!(
+            "the saved {key:?} could not be restored"
+        )));
     }
+    value
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn fuel_table_file_dialog() -> FileDialog {
+    FileDialog::new().add_filter("Fuel Table", &["json"])
+}
+
+#[cfg(target_arch = "wasm32")]
+fn fuel_table_file_dialog() -> AsyncFileDialog {
+    AsyncFileDialog::new().add_filter("Fuel Table", &["json"])
 }
@@ -7,7 +7,8 @@ use std::{
 
 use egui::{
     text::{CCursor, CCursorRange},
-    Align, DragValue, Layout, Response, Separator, TextEdit, Ui, Widget,
+    Align, ComboBox, DragValue, Layout, Response, RichText, Separator, SidePanel, TextEdit, Ui,
+    Widget,
 };
 use egui_extras::{Column, TableBuilder};
 use enum_map::{Enum, EnumMap};
@@ -16,27 +17,49 @@ use itertools::Itertools;
 use log::debug;
 use malachite::{
     num::{
+        arithmetic::traits::Abs,
         basic::traits::{One, Zero},
-        conversion::{string::options::ToSciOptions, traits::ToSci},
+        conversion::{
+            string::options::ToSciOptions,
+            traits::{RoundingFrom, ToSci},
+        },
     },
+    rounding_modes::RoundingMode,
     Rational,
 };
+use serde::{Deserialize, Serialize};
 
+use super::app::Notification;
 use crate::model::{
-    machine::{ClockedMachine, ClockedMachines, Machines, Voltage},
-    processing_chain::{ProcessingChain, Setup},
-    recipe::{Machine, Product, ProductCount, Recipe},
+    fuel::{FuelError, FuelTable},
+    machine::{
+        ClockedMachine, ClockedMachines, Coil, MachineGroup, MachinePowerError, Machines,
+        Overclocking, SteamMachines, SteamTier, Voltage,
+    },
+    machine_counts::{MachineCountError, MachineCountSolver},
+    processing_chain::{
+        Balanced, LinearProgram, NetRate, Objective, ProcessingChain, Setup, Target, Weight,
+    },
+    recipe::{Chance, Machine, Product, ProductCount, ProductForm, Recipe},
 };
 
 const HEADER_HEIGHT: f32 = 30.0;
 const ROW_HEIGHT: f32 = 20.0;
 const ROW_SEPARATOR_HEIGHT: f32 = 7.0;
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ProcessingChainTable {
+    #[serde(with = "processing_chain_json")]
     processing_chain: ProcessingChain,
+    view_mode: ViewMode,
+    #[serde(skip)]
     rows: EnumMap<ViewMode, OnceCell<Vec<TableRow>>>,
+    #[serde(skip)]
     editing_cell: Option<((TableColumn, usize), Option<EditingBuffer>)>,
+    machine_count_solver: MachineCountSolver,
+    /// The last result of the [`MachineCountSolver`], which can be applied to the chain.
+    #[serde(skip)]
+    machine_counts: Option<Result<Vec<Option<Machines>>, MachineCountError>>,
 }
 
 impl ProcessingChainTable {
@@ -47,7 +70,33 @@ impl ProcessingChainTable {
         }
     }
 
-    pub fn show(&mut self, view_mode: ViewMode, ui: &mut Ui) {
+    /// Returns a [`Notification`] if an action could not be performed.
+    pub fn show(&mut self, fuel_table: &FuelTable, ui: &mut Ui) -> Option<Notification> {
+        let view_mode = self.view_mode;
+        if view_mode == ViewMode::Speed {
+            SidePanel::right(ui.id().with("diagnostics"))
+                .resizable(true)
+                .show_inside(ui, |ui| self.show_diagnostics(ui));
+        }
+
+        ui.add(&mut self.view_mode);
+        ui.separator();
+
+        let mut action = None;
+
+        match view_mode {
+            ViewMode::Speed => {
+                action = solver_editor(&self.processing_chain, ui);
+                action = action.or(self.show_machine_counts(ui));
+                ui.separator();
+            }
+            ViewMode::Target => {
+                action = target_editor(&self.processing_chain, ui);
+                ui.separator();
+            }
+            ViewMode::Recipe | ViewMode::Setup => {}
+        }
+
         let columns = view_mode.columns();
         let mut table_builder = TableBuilder::new(ui)
             .id_salt(view_mode)
@@ -58,8 +107,6 @@ impl ProcessingChainTable {
             table_builder = table_builder.column(column.table_builder_column());
         }
 
-        let mut action = None;
-
         table_builder
             .header(HEADER_HEIGHT, |mut header| {
                 for column in columns {
@@ -94,6 +141,7 @@ impl ProcessingChainTable {
                                             ui,
                                             view_mode,
                                             &self.processing_chain,
+                                            fuel_table,
                                             editing_buffer,
                                         ) {
                                             action.get_or_insert(new_action);
@@ -114,14 +162,149 @@ impl ProcessingChainTable {
                 });
             });
 
-        if let Some(action) = action {
-            for view_mode in action.execute(&mut self.processing_chain) {
-                self.rows[view_mode] = Default::default();
+        let action = action?;
+        self.machine_counts = None;
+        match action.execute(&mut self.processing_chain, fuel_table) {
+            Ok(view_modes) => {
+                for view_mode in view_modes {
+                    self.rows[view_mode] = Default::default();
+                }
+                None
+            }
+            Err(error) => Some(Notification::Error(error.to_string())),
+        }
+    }
+
+    /// Explains the speeds of the [`ProcessingChain`], e.g. why some setups are not running.
+    fn show_diagnostics(&self, ui: &mut Ui) {
+        ui.heading("Diagnostics 🩺");
+        ui.separator();
+
+        let processing_chain = &self.processing_chain;
+        if let Some(optimized_speeds) = processing_chain.optimized_speeds() {
+            ui.label("Speeds are optimized by the solver instead of being balanced exactly.");
+            if let Err(error) = optimized_speeds {
+                ui.label(format!("⚠ No solution: {error}"));
+            }
+            return;
+        }
+
+        let setup_text = |index: usize| {
+            format!(
+                "#{} {}",
+                index + 1,
+                processing_chain.setups()[index].recipe.machine.name
+            )
+        };
+
+        let speeds = processing_chain.speeds();
+        let diagnostics = speeds.diagnostics();
+        ui.label(format!("Rank: {}", diagnostics.rank))
+            .on_hover_text("The number of independent balance equations.");
+        ui.label(format!(
+            "Free Dimensions: {}",
+            diagnostics.free_setups.len()
+        ))
+        .on_hover_text("How many setups can run at an arbitrary speed.");
+
+        if diagnostics.free_setups.is_empty() && !processing_chain.setups().is_empty() {
+            ui.label("⚠ All setups are forced to stop.");
+        } else if diagnostics.free_setups.len() > 1 {
+            ui.label("Weights decide between:");
+            for &index in &diagnostics.free_setups {
+                ui.label(format!("• {}", setup_text(index)));
+            }
+        }
+
+        if !diagnostics.zero_setups.is_empty() {
+            ui.separator();
+            ui.label("Stopped Setups");
+            for (&index, reasons) in &diagnostics.zero_setups {
+                let reasons = reasons
+                    .iter()
+                    .map(|balanced| match balanced {
+                        Balanced::Product(product) => product.name.as_str(),
+                        Balanced::Eu => "EU",
+                    })
+                    .join(", ");
+                ui.label(format!("⚠ {}", setup_text(index)))
+                    .on_hover_text(format!("Forced to zero by balancing: {reasons}"));
+            }
+        }
+
+        if diagnostics.components.len() > 1 {
+            ui.separator();
+            ui.label("Independent Groups").on_hover_text(
+                "Setups that do not share any balanced products.\n\
+                Each group runs at full speed on its own.",
+            );
+            for (group, setups) in diagnostics.components.iter().enumerate() {
+                ui.label(format!(
+                    "{}. {}",
+                    group + 1,
+                    setups.iter().map(|&index| setup_text(index)).join(", ")
+                ));
             }
         }
     }
 
-    fn processing_chain(&self) -> &ProcessingChain {
+    fn show_machine_counts(&mut self, ui: &mut Ui) -> Option<Action> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.heading("Machine Counts 🔢");
+
+            let solver = &mut self.machine_count_solver;
+            let mut max_imbalance = u8::rounding_from(
+                &(&solver.max_imbalance * Rational::from(100)),
+                RoundingMode::Nearest,
+            )
+            .0;
+            ui.label("Max Imbalance");
+            if ui
+                .add(DragValue::new(&mut max_imbalance).range(0..=99).suffix("%"))
+                .on_hover_text("How much slower than the fastest setup any setup may run.")
+                .changed()
+            {
+                solver.max_imbalance = Rational::from_unsigneds(max_imbalance, 100);
+            }
+            ui.label("Max Machines");
+            ui.add(DragValue::new(&mut solver.max_machines))
+                .on_hover_text("The maximum number of machines per setup.");
+
+            if ui
+                .button("Solve")
+                .on_hover_text("Finds the smallest whole number of machines for each setup.")
+                .clicked()
+            {
+                self.machine_counts = Some(solver.solve(&self.processing_chain));
+            }
+
+            match &self.machine_counts {
+                Some(Ok(machines)) => {
+                    let apply = ui.button("✔ Apply").on_hover_ui(|ui| {
+                        for (setup, machines) in self.processing_chain.setups().iter().zip(machines)
+                        {
+                            ui.label(format!(
+                                "{}: {}",
+                                setup.recipe.machine.name,
+                                machines.as_ref().map_or("unchanged".into(), machines_text)
+                            ));
+                        }
+                    });
+                    action = apply.clicked().then(|| Action::SetMachines {
+                        machines: machines.clone(),
+                    });
+                }
+                Some(Err(error)) => {
+                    ui.label("⚠").on_hover_text(error.to_string());
+                }
+                None => {}
+            }
+        });
+        action
+    }
+
+    pub fn processing_chain(&self) -> &ProcessingChain {
         &self.processing_chain
     }
 
@@ -140,18 +323,28 @@ impl ProcessingChainTable {
             debug!("Building {view_mode:?} table rows for {count} setups.");
 
             let unthrottled_speed = Rational::ONE;
+            let stopped_speed = Rational::ZERO;
             let speeds: &mut dyn Iterator<Item = _> = match view_mode {
                 ViewMode::Recipe | ViewMode::Setup => &mut repeat_n(&unthrottled_speed, count),
                 ViewMode::Speed => &mut processing_chain.weighted_speeds().speeds().iter(),
+                ViewMode::Target => match processing_chain.target_speeds() {
+                    Some(target_speeds) => &mut target_speeds.iter(),
+                    None => &mut repeat_n(&stopped_speed, count),
+                },
             };
 
+            let diagnostics =
+                (view_mode == ViewMode::Speed).then(|| processing_chain.speeds().diagnostics());
+
             processing_chain
                 .setups()
                 .iter()
                 .zip_eq(speeds)
                 .enumerate()
                 .flat_map(|(index, (setup, speed))| {
-                    TableRow::from_setup(view_mode, index, setup, speed)
+                    let component =
+                        diagnostics.and_then(|diagnostics| diagnostics.component(index));
+                    TableRow::from_setup(view_mode, index, setup, speed, component)
                 })
                 .chain(TableRow::total(view_mode, processing_chain))
                 .collect::<Vec<_>>()
@@ -160,16 +353,19 @@ impl ProcessingChainTable {
 }
 
 /// The mode at which the [`ProcessingChain`] is viewed.
-#[derive(Debug, Hash, PartialOrd, Ord, Enum, EnumSetType)]
-pub enum ViewMode {
+#[derive(Debug, Default, Hash, PartialOrd, Ord, Enum, EnumSetType, Serialize, Deserialize)]
+enum ViewMode {
+    #[default]
     Recipe,
     Setup,
     Speed,
+    Target,
 }
 
 impl ViewMode {
     const NONE: EnumSet<Self> = EnumSet::empty();
-    const CALCULATED: EnumSet<Self> = enum_set![ViewMode::Setup | ViewMode::Speed];
+    const CALCULATED: EnumSet<Self> =
+        enum_set![ViewMode::Setup | ViewMode::Speed | ViewMode::Target];
     const ALL: EnumSet<Self> = EnumSet::all();
 
     const fn name(self) -> &'static str {
@@ -177,6 +373,7 @@ impl ViewMode {
             ViewMode::Recipe => "Recipe",
             ViewMode::Setup => "Setup",
             ViewMode::Speed => "Speed",
+            ViewMode::Target => "Target",
         }
     }
 
@@ -185,6 +382,7 @@ impl ViewMode {
             ViewMode::Recipe => "Shows information about only the recipes.",
             ViewMode::Setup => "Shows information based on a specific machine setup.",
             ViewMode::Speed => "Shows information based on the effective speed of machines.",
+            ViewMode::Target => "Shows how many machines are required to reach a target rate.",
         }
     }
 
@@ -215,6 +413,19 @@ impl ViewMode {
                     | TableColumn::Setup
                     | TableColumn::Catalysts
                     | TableColumn::Speed
+                    | TableColumn::Weight
+                    | TableColumn::Component
+                    | TableColumn::Consumed
+                    | TableColumn::ConsumedCount
+                    | TableColumn::Produced
+                    | TableColumn::ProducedCount
+                    | TableColumn::Eu
+            ],
+            Self::Target => enum_set![
+                TableColumn::Machine
+                    | TableColumn::Setup
+                    | TableColumn::Required
+                    | TableColumn::Catalysts
                     | TableColumn::Consumed
                     | TableColumn::ConsumedCount
                     | TableColumn::Produced
@@ -229,7 +440,12 @@ impl Widget for &mut ViewMode {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.horizontal(|ui| {
             ui.heading("View Mode");
-            for view_mode in [ViewMode::Recipe, ViewMode::Setup, ViewMode::Speed] {
+            for view_mode in [
+                ViewMode::Recipe,
+                ViewMode::Setup,
+                ViewMode::Speed,
+                ViewMode::Target,
+            ] {
                 ui.selectable_value(self, view_mode, view_mode.name())
                     .on_hover_text(view_mode.description());
             }
@@ -257,11 +473,18 @@ impl TableRow {
         index: usize,
         setup: &'a Setup,
         speed: &'a Rational,
+        component: Option<usize>,
     ) -> impl Iterator<Item = Self> + 'a {
         let mut machine_col = once(SetupTableCellContent::Machine);
 
         let mut machines_col: Box<dyn Iterator<Item = _>> = match &setup.machines {
             Machines::Eco(_) => Box::new(once(SetupTableCellContent::SetupEco)),
+            Machines::Steam(steam_machines) => Box::new(
+                steam_machines
+                    .steam
+                    .keys()
+                    .map(|&tier| SetupTableCellContent::SetupSteam { tier }),
+            ),
             Machines::Power(clocked_machines) => Box::new(
                 clocked_machines
                     .machines
@@ -275,6 +498,12 @@ impl TableRow {
 
         let mut speed_col = once(SetupTableCellContent::Speed);
 
+        let mut weight_col = once(SetupTableCellContent::Weight);
+
+        let mut component_col = component.map(SetupTableCellContent::Component).into_iter();
+
+        let mut required_col = once(SetupTableCellContent::Required);
+
         let mut consumed_col =
             (0..setup.recipe.consumed.len()).map(|index| SetupTableCellContent::Consumed { index });
         let mut consumed_count_col: Box<dyn Iterator<Item = _>> = match view_mode {
@@ -282,12 +511,14 @@ impl TableRow {
                 (0..setup.recipe.consumed.len())
                     .map(|index| SetupTableCellContent::ConsumedCount { index }),
             ),
-            ViewMode::Setup | ViewMode::Speed => Box::new(SetupTableCellContent::product_amounts(
-                &setup.recipe.consumed,
-                setup,
-                speed,
-                |index, amount| SetupTableCellContent::ConsumedAmount { index, amount },
-            )),
+            ViewMode::Setup | ViewMode::Speed | ViewMode::Target => {
+                Box::new(SetupTableCellContent::product_amounts(
+                    &setup.recipe.consumed,
+                    setup,
+                    speed,
+                    |index, amount| SetupTableCellContent::ConsumedAmount { index, amount },
+                ))
+            }
         };
 
         let mut produced_col =
@@ -297,25 +528,27 @@ impl TableRow {
                 (0..setup.recipe.produced.len())
                     .map(|index| SetupTableCellContent::ProducedCount { index }),
             ),
-            ViewMode::Setup | ViewMode::Speed => Box::new(SetupTableCellContent::product_amounts(
-                &setup.recipe.produced,
-                setup,
-                speed,
-                |index, amount| SetupTableCellContent::ProducedAmount { index, amount },
-            )),
+            ViewMode::Setup | ViewMode::Speed | ViewMode::Target => {
+                Box::new(SetupTableCellContent::product_amounts(
+                    &setup.recipe.produced,
+                    setup,
+                    speed,
+                    |index, amount| SetupTableCellContent::ProducedAmount { index, amount },
+                ))
+            }
         };
 
         let mut time_col = once(SetupTableCellContent::Time);
 
         let mut eu_col = once_with(move || match view_mode {
             ViewMode::Recipe => SetupTableCellContent::EuPerTickRecipe,
-            ViewMode::Setup => match setup.machines.eu_per_tick(setup.recipe.eu_per_tick) {
+            ViewMode::Setup => match setup.eu_per_tick() {
                 Ok(eu) => SetupTableCellContent::EuPerTick(Box::new(eu.into())),
-                Err(_) => SetupTableCellContent::PowerError,
+                Err(error) => SetupTableCellContent::PowerError(error),
             },
-            ViewMode::Speed => match setup.machines.eu_per_tick(setup.recipe.eu_per_tick) {
+            ViewMode::Speed | ViewMode::Target => match setup.eu_per_tick() {
                 Ok(eu) => SetupTableCellContent::EuPerTick(Box::new(Rational::from(eu) * speed)),
-                Err(_) => SetupTableCellContent::PowerError,
+                Err(error) => SetupTableCellContent::PowerError(error),
             },
         });
 
@@ -331,6 +564,9 @@ impl TableRow {
                             TableColumn::Setup => machines_col.next(),
                             TableColumn::Catalysts => catalysts_col.next(),
                             TableColumn::Speed => speed_col.next(),
+                            TableColumn::Weight => weight_col.next(),
+                            TableColumn::Component => component_col.next(),
+                            TableColumn::Required => required_col.next(),
                             TableColumn::Consumed => consumed_col.next(),
                             TableColumn::ConsumedCount => consumed_count_col.next(),
                             TableColumn::Produced => produced_col.next(),
@@ -360,6 +596,7 @@ impl TableRow {
             ViewMode::Speed => {
                 Some(processing_chain.products_with_speeds(processing_chain.weighted_speeds()))
             }
+            ViewMode::Target => processing_chain.products_with_target_speeds(),
         };
 
         products.into_iter().flat_map(move |products| {
@@ -371,9 +608,13 @@ impl TableRow {
                 .into_iter()
                 .filter(|(_, amount)| *amount < 0)
                 .map(|(product, amount)| {
+                    let form = product.form;
                     (
                         TotalTableCellContent::Product(product),
-                        TotalTableCellContent::ProductAmount(Box::new(-amount)),
+                        TotalTableCellContent::ProductAmount {
+                            form,
+                            amount: Box::new(-amount),
+                        },
                     )
                 });
 
@@ -382,9 +623,13 @@ impl TableRow {
                 .into_iter()
                 .filter(|(_, amount)| *amount > 0)
                 .map(|(product, amount)| {
+                    let form = product.form;
                     (
                         TotalTableCellContent::Product(product),
-                        TotalTableCellContent::ProductAmount(Box::new(amount)),
+                        TotalTableCellContent::ProductAmount {
+                            form,
+                            amount: Box::new(amount),
+                        },
                     )
                 });
 
@@ -406,6 +651,9 @@ impl TableRow {
                                 TableColumn::Setup => None,
                                 TableColumn::Catalysts => None,
                                 TableColumn::Speed => None,
+                                TableColumn::Weight => None,
+                                TableColumn::Component => None,
+                                TableColumn::Required => None,
                                 TableColumn::Consumed => consumed.take(),
                                 TableColumn::ConsumedCount => consumed_amount.take(),
                                 TableColumn::Produced => produced.take(),
@@ -433,6 +681,9 @@ enum TableColumn {
     Catalysts,
     Setup,
     Speed,
+    Weight,
+    Component,
+    Required,
     Time,
     Eu,
     Consumed,
@@ -448,6 +699,9 @@ impl TableColumn {
             Self::Catalysts => "Catalysts 🔥",
             Self::Setup => "Setup 📜",
             Self::Speed => "Speed ⏱",
+            Self::Weight => "Weight ⚖",
+            Self::Component => "Group 🔗",
+            Self::Required => "Required 🎯",
             Self::Consumed => "Consumed",
             Self::ConsumedCount => "📦",
             Self::Produced => "Produced",
@@ -463,30 +717,42 @@ impl TableColumn {
             Self::Catalysts => "Products that are required but not consumed.",
             Self::Setup => "The machines processing this recipe.",
             Self::Speed => "How fast this machine can run.",
+            Self::Weight => "How products are shared with other machines.",
+            Self::Component => {
+                "Machines in the same group share products and are normalized together."
+            }
+            Self::Required => "How many machines are required to reach the target rate.",
             Self::Consumed | Self::ConsumedCount => match view_mode {
                 ViewMode::Recipe => "Consumed products per processing cycle.",
                 ViewMode::Setup => "Consumed products by all machines.",
                 ViewMode::Speed => "Consumed products at the current speed.",
+                ViewMode::Target => "Consumed products at the target rate.",
             },
             Self::Produced | Self::ProducedCount => match view_mode {
                 ViewMode::Recipe => "Produced products per processing cycle.",
                 ViewMode::Setup => "Produced procuts by all machines.",
                 ViewMode::Speed => "Produced products at the current speed.",
+                ViewMode::Target => "Produced products at the target rate.",
             },
             Self::Time => "Duration of a single processing cycle.",
             Self::Eu => match view_mode {
                 ViewMode::Recipe => "EU/t for a single machine at its minimum voltage.",
                 ViewMode::Setup => "EU/t of all machines.",
                 ViewMode::Speed => "EU/t at the current speed.",
+                ViewMode::Target => "EU/t at the target rate.",
             },
         }
     }
 
     fn table_builder_column(self) -> Column {
         match self {
-            Self::Catalysts | Self::Eu | Self::ConsumedCount | Self::ProducedCount => {
-                Column::auto()
-            }
+            Self::Catalysts
+            | Self::Weight
+            | Self::Component
+            | Self::Required
+            | Self::Eu
+            | Self::ConsumedCount
+            | Self::ProducedCount => Column::auto(),
             _ => Column::auto_with_initial_suggestion(0.0)
                 .resizable(true)
                 .clip(true),
@@ -511,25 +777,19 @@ impl TableCell {
         ui: &mut Ui,
         view_mode: ViewMode,
         processing_chain: &ProcessingChain,
+        fuel_table: &FuelTable,
         editing_buffer: &mut Option<EditingBuffer>,
     ) -> Option<Action> {
         match self {
-            Self::Setup { index, content } => content
-                .show(
-                    view_mode,
-                    &processing_chain.setups()[*index],
-                    || &processing_chain.weighted_speeds().speeds()[*index],
-                    editing_buffer,
-                    ui,
-                )
-                .map(|action| Action::Setup {
-                    index: *index,
-                    action,
-                }),
-            Self::Total { content } => {
-                content.show(ui);
-                None
-            }
+            Self::Setup { index, content } => content.show(
+                view_mode,
+                *index,
+                processing_chain,
+                fuel_table,
+                editing_buffer,
+                ui,
+            ),
+            Self::Total { content } => content.show(processing_chain, editing_buffer, ui),
         }
     }
 }
@@ -539,9 +799,13 @@ enum SetupTableCellContent {
     Machine,
     Catalyst { index: usize },
     SetupEco,
+    SetupSteam { tier: SteamTier },
     SetupPower { clocked_machine: ClockedMachine },
     Time,
     Speed,
+    Weight,
+    Component(usize),
+    Required,
     EuPerTickRecipe,
     EuPerTick(Box<Rational>),
     Produced { index: usize },
@@ -550,7 +814,7 @@ enum SetupTableCellContent {
     ProducedCount { index: usize },
     ConsumedAmount { index: usize, amount: Box<Rational> },
     ProducedAmount { index: usize, amount: Box<Rational> },
-    PowerError,
+    PowerError(MachinePowerError),
 }
 
 impl SetupTableCellContent {
@@ -563,38 +827,38 @@ impl SetupTableCellContent {
         product_counts
             .iter()
             .enumerate()
-            .map(move |(index, product_count)| {
-                match setup.machines.speed_factor(setup.recipe.voltage()) {
-                    Ok(speed_factor) => new(
-                        index,
-                        Box::new(
-                            Rational::from(product_count.count.get()) / setup.recipe.seconds()
-                                * speed_factor
-                                * speed,
-                        ),
-                    ),
-                    Err(_) => Self::PowerError,
-                }
-            })
+            .map(
+                move |(index, product_count)| match setup.product_count_per_sec(product_count) {
+                    Ok(amount) => new(index, Box::new(amount * speed)),
+                    Err(error) => Self::PowerError(error),
+                },
+            )
     }
 
-    fn show<'a>(
+    fn show(
         &self,
         view_mode: ViewMode,
-        setup: &'a Setup,
-        speed: impl FnOnce() -> &'a Rational,
+        setup_index: usize,
+        processing_chain: &ProcessingChain,
+        fuel_table: &FuelTable,
         editing_buffer: &mut Option<EditingBuffer>,
         ui: &mut Ui,
-    ) -> Option<SetupAction> {
-        match self {
-            Self::Machine => editable_machine(view_mode, &setup.recipe.machine, editing_buffer, ui),
-            Self::Catalyst { index } => editable_product(
-                &setup.recipe.catalysts[*index],
-                editing_buffer,
-                *index,
-                ProductKind::Catalyst,
-                ui,
-            ),
+    ) -> Option<Action> {
+        let setup = &processing_chain.setups()[setup_index];
+        let speed = || &processing_chain.weighted_speeds().speeds()[setup_index];
+        let action = match self {
+            Self::Machine => editable_machine(view_mode, setup, fuel_table, editing_buffer, ui),
+            Self::Catalyst { index } => {
+                return editable_product(
+                    &setup.recipe.catalysts[*index],
+                    None,
+                    editing_buffer,
+                    setup_index,
+                    *index,
+                    ProductKind::Catalyst,
+                    ui,
+                );
+            }
             Self::SetupEco => {
                 if let Machines::Eco(count) = setup.machines {
                     editable_eco_machine(count, ui)
@@ -602,9 +866,16 @@ impl SetupTableCellContent {
                     unreachable!();
                 }
             }
+            Self::SetupSteam { tier } => {
+                if let Machines::Steam(steam_machines) = &setup.machines {
+                    editable_steam_machine(steam_machines, *tier, &setup.recipe, ui)
+                } else {
+                    unreachable!();
+                }
+            }
             Self::SetupPower { clocked_machine } => {
                 if let Machines::Power(clocked_machines) = &setup.machines {
-                    editable_power_machine(clocked_machines, *clocked_machine, ui)
+                    editable_power_machine(clocked_machines, *clocked_machine, &setup.recipe, ui)
                 } else {
                     unreachable!();
                 }
@@ -617,25 +888,55 @@ impl SetupTableCellContent {
                 ui.label(format!("{}%", speed_percent.to_sci_with_options(options)));
                 None
             }
-            Self::EuPerTickRecipe => editable_eu_per_tick(setup.recipe.eu_per_tick, ui),
+            Self::Weight => editable_weight(processing_chain, setup_index, ui),
+            Self::Component(component) => {
+                ui.label(format!("{}", component + 1));
+                None
+            }
+            Self::Required => {
+                match (
+                    processing_chain.target_speeds(),
+                    processing_chain.required_machines(),
+                ) {
+                    (Some(target_speeds), Some(required)) => required_machines(
+                        &setup.machines,
+                        &target_speeds[setup_index],
+                        &required[setup_index],
+                        ui,
+                    ),
+                    _ => {
+                        ui.label("—");
+                    }
+                }
+                None
+            }
+            Self::EuPerTickRecipe => editable_eu_per_tick(&setup.recipe, ui),
             Self::EuPerTick(eu) => {
                 eu_per_tick(ui, eu);
                 None
             }
-            Self::Consumed { index } => editable_product(
-                &setup.recipe.consumed[*index].product,
-                editing_buffer,
-                *index,
-                ProductKind::Consumed,
-                ui,
-            ),
-            Self::Produced { index } => editable_product(
-                &setup.recipe.produced[*index].product,
-                editing_buffer,
-                *index,
-                ProductKind::Produced,
-                ui,
-            ),
+            Self::Consumed { index } => {
+                return editable_product(
+                    &setup.recipe.consumed[*index].product,
+                    Some(processing_chain),
+                    editing_buffer,
+                    setup_index,
+                    *index,
+                    ProductKind::Consumed,
+                    ui,
+                );
+            }
+            Self::Produced { index } => {
+                return editable_product(
+                    &setup.recipe.produced[*index].product,
+                    Some(processing_chain),
+                    editing_buffer,
+                    setup_index,
+                    *index,
+                    ProductKind::Produced,
+                    ui,
+                );
+            }
             Self::ConsumedCount { index } => {
                 editable_count(setup.recipe.consumed[*index].count, ui, |count| {
                     SetupAction::SetConsumedCount {
@@ -645,15 +946,26 @@ impl SetupTableCellContent {
                 })
             }
             Self::ProducedCount { index } => {
-                editable_count(setup.recipe.produced[*index].count, ui, |count| {
+                let product_count = &setup.recipe.produced[*index];
+                let chance_action = editable_chance(
+                    product_count.chance,
+                    setup.recipe.chance_boost,
+                    ui,
+                    |chance| SetupAction::SetProducedChance {
+                        index: *index,
+                        chance,
+                    },
+                );
+                let count_action = editable_count(product_count.count, ui, |count| {
                     SetupAction::SetProducedCount {
                         index: *index,
                         count,
                     }
-                })
+                });
+                chance_action.or(count_action)
             }
             Self::ConsumedAmount { index, amount } => {
-                editable_amount(setup.recipe.consumed[*index].count, amount, ui, |count| {
+                editable_amount(&setup.recipe.consumed[*index], amount, ui, |count| {
                     SetupAction::SetConsumedCount {
                         index: *index,
                         count,
@@ -661,44 +973,187 @@ impl SetupTableCellContent {
                 })
             }
             Self::ProducedAmount { index, amount } => {
-                editable_amount(setup.recipe.produced[*index].count, amount, ui, |count| {
+                editable_amount(&setup.recipe.produced[*index], amount, ui, |count| {
                     SetupAction::SetProducedCount {
                         index: *index,
                         count,
                     }
                 })
             }
-            Self::PowerError => {
+            Self::PowerError(error) => {
                 ui.label("⚠")
-                    .on_hover_text(match setup.recipe.eu_per_tick.cmp(&0) {
-                        Ordering::Less => "This recipe requires a machine that consumes power.",
-                        Ordering::Equal => "This recipe requires machine without voltage.",
-                        Ordering::Greater => "This recipe requires a machine that produces power.",
-                    });
+                    .on_hover_text(power_error_text(*error, &setup.recipe));
                 None
             }
+        };
+        action.map(|action| Action::Setup {
+            index: setup_index,
+            action,
+        })
+    }
+}
+
+fn power_error_text(error: MachinePowerError, recipe: &Recipe) -> String {
+    match error {
+        MachinePowerError::RequiresEco | MachinePowerError::RequiresPower => {
+            match recipe.eu_per_tick.cmp(&0) {
+                Ordering::Less => "This recipe requires a machine that consumes power.",
+                Ordering::Equal => "This recipe requires machine without voltage.",
+                Ordering::Greater => "This recipe requires a machine that produces power.",
+            }
+            .to_string()
+        }
+        MachinePowerError::SteamGenerator => {
+            "Steam machines cannot run a recipe that produces power.".to_string()
         }
+        MachinePowerError::RequiresCoils => {
+            "This recipe requires a machine with coils.".to_string()
+        }
+        MachinePowerError::InsufficientHeat {
+            required,
+            available,
+        } => format!("This recipe requires {required}K, but the coils only reach {available}K."),
+        MachinePowerError::InsufficientPower {
+            required,
+            available,
+        } => format!(
+            "This recipe requires {required} EU/t, \
+            but the machines can only draw {available} EU/t."
+        ),
+        MachinePowerError::InsufficientAmperage {
+            required,
+            available,
+        } => format!(
+            "This recipe requires {required}A, but the machines can only draw {available}A."
+        ),
+        MachinePowerError::InsufficientTier {
+            required,
+            available,
+        } => format!("This recipe requires {required}, but the machine only runs at {available}."),
+    }
+}
+
+fn clocking_text(clocked_machine: ClockedMachine) -> String {
+    let tier = clocked_machine.tier();
+    let underclocking = clocked_machine.underclocking();
+    if tier == underclocking {
+        format!("🏭{tier}")
+    } else {
+        format!("🏭{tier}⤵{underclocking}")
+    }
+}
+
+fn machine_group_text(group: MachineGroup) -> String {
+    match group {
+        MachineGroup::Eco => "🏭".into(),
+        MachineGroup::Steam(tier) => format!("🏭{}", tier.name()),
+        MachineGroup::Power(clocked_machine) => clocking_text(clocked_machine),
+    }
+}
+
+fn machines_text(machines: &Machines) -> String {
+    match machines {
+        Machines::Eco(count) => format!("🏭 ×{count}"),
+        Machines::Steam(steam_machines) => steam_machines
+            .steam
+            .iter()
+            .map(|(tier, count)| format!("🏭{} ×{count}", tier.name()))
+            .join(" + "),
+        Machines::Power(clocked_machines) => clocked_machines
+            .machines
+            .iter()
+            .map(|(clocked_machine, count)| format!("{} ×{count}", clocking_text(*clocked_machine)))
+            .join(" + "),
     }
 }
 
 fn editable_power_machine(
     clocked_machines: &ClockedMachines,
     clocked_machine: ClockedMachine,
+    recipe: &Recipe,
     ui: &mut Ui,
 ) -> Option<SetupAction> {
     let old_count = clocked_machines.machines[&clocked_machine];
     let mut count = old_count;
 
-    let tier = clocked_machine.tier();
     let underclocking = clocked_machine.underclocking();
+    let parallels = clocked_machine.parallels();
+    let effective_parallels = recipe.voltage().map(|recipe_voltage| {
+        clocked_machines.effective_parallels(&clocked_machine, recipe_voltage, recipe)
+    });
+
+    let clocking = clocking_text(clocked_machine);
+    let prefix = match effective_parallels {
+        _ if parallels == NonZeroU64::MIN => format!("{clocking} ×"),
+        Some(Ok(effective_parallels)) if effective_parallels != parallels => {
+            format!("{clocking} ∥{effective_parallels}/{parallels} ×")
+        }
+        _ => format!("{clocking} ∥{parallels} ×"),
+    };
 
     let mut action = None;
-    ui.add(DragValue::new(&mut count).prefix(if tier == underclocking {
-        format!("🏭{tier} ×")
-    } else {
-        format!("🏭{tier}⤵{underclocking} ×")
-    }))
-    .context_menu(|ui| {
+    let response = ui.add(DragValue::new(&mut count).prefix(prefix));
+    let response = match effective_parallels {
+        Some(Err(error)) => {
+            ui.label("⚠").on_hover_text(power_error_text(error, recipe));
+            response
+        }
+        Some(Ok(effective_parallels)) if effective_parallels != parallels => response
+            .on_hover_text(format!(
+                "Only {effective_parallels} of {parallels} parallels fit into {underclocking}."
+            )),
+        _ => response,
+    };
+    response.context_menu(|ui| {
+        ui.menu_button(
+            clocked_machines
+                .coil
+                .map_or("🔥 Coils".into(), |coil| format!("🔥 {}", coil.name())),
+            |ui| {
+                let mut coil = None;
+                if ui.radio(clocked_machines.coil.is_none(), "None").clicked() {
+                    coil = Some(None);
+                }
+                ui.separator();
+                for coil_index in 0..Coil::LENGTH {
+                    let new_coil = Coil::from_usize(coil_index);
+                    if ui
+                        .radio(
+                            clocked_machines.coil == Some(new_coil),
+                            format!("{} ({}K)", new_coil.name(), new_coil.heat()),
+                        )
+                        .clicked()
+                    {
+                        coil = Some(Some(new_coil));
+                    }
+                }
+                if let Some(coil) = coil {
+                    ui.close_menu();
+                    action = Some(SetupAction::SetCoil { coil });
+                }
+            },
+        );
+        ui.horizontal(|ui| {
+            ui.label("Amperage:");
+            let mut amperage = clocked_machines.amperage;
+            ui.add(DragValue::new(&mut amperage).suffix("A"))
+                .on_hover_text("How many amps each machine can draw, e.g. from energy hatches.");
+            if amperage != clocked_machines.amperage {
+                action = Some(SetupAction::SetMachineAmperage { amperage });
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Parallels:");
+            let mut new_parallels = parallels;
+            ui.add(DragValue::new(&mut new_parallels).prefix("∥"));
+            if new_parallels != parallels {
+                action = Some(SetupAction::SetParallels {
+                    clocked_machine,
+                    parallels: new_parallels,
+                });
+            }
+        });
+        ui.separator();
         ui.menu_button("🏭 Add", setup_selector(&mut action));
         ui.separator();
         if ui.button("❌ Remove").clicked() {
@@ -746,15 +1201,246 @@ fn editable_eco_machine(count: u64, ui: &mut Ui) -> Option<SetupAction> {
     action
 }
 
-fn editable_eu_per_tick(eu_per_tick: i64, ui: &mut Ui) -> Option<SetupAction> {
-    let mut new_eu_per_tick = eu_per_tick;
-    ui.add(DragValue::new(&mut new_eu_per_tick).suffix(" EU/t"));
-    (new_eu_per_tick != eu_per_tick).then_some(SetupAction::SetEuPerTick {
-        eu_per_tick: new_eu_per_tick,
+fn editable_steam_machine(
+    steam_machines: &SteamMachines,
+    tier: SteamTier,
+    recipe: &Recipe,
+    ui: &mut Ui,
+) -> Option<SetupAction> {
+    let old_count = steam_machines.steam[&tier].get();
+    let mut count = old_count;
+    let mut action = None;
+    ui.add(DragValue::new(&mut count).prefix(format!("🏭{} ×", tier.name())))
+        .on_hover_ui(|ui| {
+            let steam_per_sec = tier.steam_per_tick(recipe.eu_per_tick.unsigned_abs()) * 20;
+            ui.label(format!(
+                "Consumes {steam_per_sec} L/s of steam per machine."
+            ));
+        })
+        .context_menu(|ui| {
+            ui.menu_button("🏭 Add", setup_selector(&mut action));
+            ui.separator();
+            if ui.button("❌ Remove").clicked() {
+                ui.close_menu();
+                action = Some(SetupAction::SetSteamMachineCount { tier, count: 0 });
+            }
+        });
+
+    if count != old_count {
+        action = Some(SetupAction::SetSteamMachineCount { tier, count });
+    }
+
+    action
+}
+
+fn editable_eu_per_tick(recipe: &Recipe, ui: &mut Ui) -> Option<SetupAction> {
+    let mut new_eu_per_tick = recipe.eu_per_tick;
+    let mut action = None;
+    ui.add(DragValue::new(&mut new_eu_per_tick).suffix(" EU/t"))
+        .on_hover_text(recipe.overclocking.name())
+        .context_menu(|ui| {
+            for overclocking in [
+                Overclocking::Normal,
+                Overclocking::Perfect,
+                Overclocking::None,
+            ] {
+                if ui
+                    .radio(recipe.overclocking == overclocking, overclocking.name())
+                    .on_hover_text(overclocking.description())
+                    .clicked()
+                {
+                    ui.close_menu();
+                    action = Some(SetupAction::SetOverclocking { overclocking });
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Amperage:");
+                let mut amperage = recipe.amperage;
+                ui.add(DragValue::new(&mut amperage).suffix("A"))
+                    .on_hover_text("How many amps the EU/t is split across.");
+                if amperage != recipe.amperage {
+                    action = Some(SetupAction::SetAmperage { amperage });
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Heat:");
+                let mut heat = recipe.heat.map_or(0, NonZeroU64::get);
+                if ui
+                    .add(DragValue::new(&mut heat).suffix("K"))
+                    .on_hover_text("Minimum heat required by machines with coils.")
+                    .changed()
+                {
+                    action = Some(SetupAction::SetHeat {
+                        heat: NonZeroU64::new(heat),
+                    });
+                }
+            });
+            ui.separator();
+            let mut subtick_parallels = recipe.subtick_parallels;
+            if ui
+                .checkbox(&mut subtick_parallels, "Subtick Parallels")
+                .on_hover_text(
+                    "Overclocking beyond a single tick processes multiple recipes at once.",
+                )
+                .changed()
+            {
+                action = Some(SetupAction::SetSubtickParallels { subtick_parallels });
+            }
+        });
+
+    if new_eu_per_tick != recipe.eu_per_tick {
+        action = Some(SetupAction::SetEuPerTick {
+            eu_per_tick: new_eu_per_tick,
+        });
+    }
+
+    action
+}
+
+/// Shows the `required` machines of each [`MachineGroup`], which are alternatives to each other.
+///
+/// Highlighted if the configured `machines` are not enough, i.e. the `target_speed` exceeds 100%.
+fn required_machines(
+    machines: &Machines,
+    target_speed: &Rational,
+    required: &[(MachineGroup, Rational)],
+    ui: &mut Ui,
+) {
+    if required.is_empty() {
+        ui.label("—");
+        return;
+    }
+
+    let mut options = ToSciOptions::default();
+    options.set_scale(2);
+    let text = required
+        .iter()
+        .map(|(group, required)| {
+            format!(
+                "{} ×{}",
+                machine_group_text(*group),
+                required.to_sci_with_options(options)
+            )
+        })
+        .join(" / ");
+    let label = if *target_speed > 1 {
+        ui.colored_label(ui.visuals().warn_fg_color, text)
+    } else {
+        ui.label(text)
+    };
+    label.on_hover_ui(|ui| {
+        ui.set_max_width(ui.spacing().tooltip_width);
+        for (group, required) in required {
+            let (machines, denominator) = required.numerator_and_denominator_ref();
+            ui.label(format!(
+                "Requires {machines} / {denominator} × {} on their own.",
+                machine_group_text(*group)
+            ));
+        }
+        ui.label(format!("Currently configured: {}", machines_text(machines)));
+    });
+}
+
+fn solver_editor(processing_chain: &ProcessingChain, ui: &mut Ui) -> Option<Action> {
+    let objective = processing_chain
+        .linear_program()
+        .map(|linear_program| &linear_program.objective);
+    let mut new_objective = objective.cloned();
+
+    ui.horizontal(|ui| {
+        ui.heading("Solver 🧮");
+        ComboBox::from_id_salt("objective")
+            .selected_text(objective.map_or("Balance".into(), objective_text))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut new_objective, None, "Balance")
+                    .on_hover_text("Balances all products exactly, taking weights into account.");
+                ui.separator();
+                for objective in [Objective::MaximizeSpeeds, Objective::MinimizeEu]
+                    .into_iter()
+                    .chain(
+                        processing_chain
+                            .products()
+                            .into_iter()
+                            .map(|product| Objective::MaximizeOutput(product.clone())),
+                    )
+                {
+                    let text = objective_text(&objective);
+                    ui.selectable_value(&mut new_objective, Some(objective), text);
+                }
+            });
+
+        if let Some(Err(error)) = processing_chain.optimized_speeds() {
+            ui.label("⚠").on_hover_text(format!("No solution: {error}"));
+        } else if processing_chain
+            .linear_program()
+            .is_some_and(LinearProgram::stops_all_setups)
+        {
+            ui.label("⚠").on_hover_text(
+                "All setups are stopped. Bound the net rate of a product to require some output.",
+            );
+        }
+    });
+
+    (new_objective.as_ref() != objective).then_some(Action::SetObjective {
+        objective: new_objective,
     })
 }
 
-fn eu_per_tick(ui: &mut Ui, eu: &Rational) {
+fn objective_text(objective: &Objective) -> String {
+    match objective {
+        Objective::MaximizeSpeeds => "Maximize Speeds".into(),
+        Objective::MaximizeOutput(product) => format!("Maximize {}", product.name),
+        Objective::MinimizeEu => "Minimize EU".into(),
+    }
+}
+
+fn target_editor(processing_chain: &ProcessingChain, ui: &mut Ui) -> Option<Action> {
+    let target = processing_chain.target();
+    let mut new_target = target.cloned();
+
+    ui.horizontal(|ui| {
+        ui.heading("Target 🎯");
+        ComboBox::from_id_salt("target_product")
+            .selected_text(target.map_or("None", |target| &target.product.name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut new_target, None, "None");
+                for product in processing_chain.products() {
+                    if ui
+                        .selectable_label(
+                            target.is_some_and(|target| target.product == *product),
+                            &product.name,
+                        )
+                        .clicked()
+                    {
+                        new_target = Some(Target {
+                            product: product.clone(),
+                            ..target
+                                .cloned()
+                                .unwrap_or_else(|| Target::new(product.clone()))
+                        });
+                    }
+                }
+            });
+
+        if let Some(new_target) = &mut new_target {
+            let unit = new_target.product.form.unit();
+            ui.add(DragValue::new(&mut new_target.count).suffix(unit));
+            ui.label("per");
+            ui.add(DragValue::new(&mut new_target.seconds).suffix(" s"));
+        }
+
+        if target.is_some() && processing_chain.target_speeds().is_none() {
+            ui.label("⚠").on_hover_text(
+                "The target product is neither an input nor an output of the processing chain.",
+            );
+        }
+    });
+
+    (new_target.as_ref() != target).then_some(Action::SetTarget { target: new_target })
+}
+
+fn eu_per_tick(ui: &mut Ui, eu: &Rational) -> Response {
     let mut options = ToSciOptions::default();
     options.set_scale(2);
     ui.label(format!("{} EU/t", eu.to_sci_with_options(options)))
@@ -770,7 +1456,7 @@ fn eu_per_tick(ui: &mut Ui, eu: &Rational) {
             };
             let (eu, ticks) = eu.numerator_and_denominator_ref();
             ui.label(format!("{dir} {eu} EU / {ticks} ticks"));
-        });
+        })
 }
 
 fn setup_selector(action: &mut Option<SetupAction>) -> impl FnOnce(&mut Ui) + '_ {
@@ -783,6 +1469,14 @@ fn setup_selector(action: &mut Option<SetupAction>) -> impl FnOnce(&mut Ui) + '_
 
         ui.separator();
 
+        for tier in [SteamTier::Bronze, SteamTier::Steel] {
+            if ui.button(format!("🏭{}", tier.name())).clicked() {
+                *action = Some(SetupAction::InsertSteamMachine { tier });
+            }
+        }
+
+        ui.separator();
+
         let mut clocked_machine = None;
         if ui.button(format!("🏭{}", Voltage::UltraLow)).clicked() {
             clocked_machine = Some(ClockedMachine::new(Voltage::UltraLow));
@@ -812,17 +1506,38 @@ fn setup_selector(action: &mut Option<SetupAction>) -> impl FnOnce(&mut Ui) + '_
     }
 }
 
+fn fuel_selector<'a>(
+    setup: &'a Setup,
+    fuel_table: &'a FuelTable,
+    action: &'a mut Option<SetupAction>,
+) -> impl FnOnce(&mut Ui) + 'a {
+    |ui| {
+        for (fuel, eu) in fuel_table.fuels(&setup.recipe.machine) {
+            if ui
+                .radio(setup.recipe.consumes(&fuel), &fuel.name)
+                .on_hover_text(format!("{eu} EU per unit"))
+                .clicked()
+            {
+                ui.close_menu();
+                *action = Some(SetupAction::SetFuel { fuel });
+            }
+        }
+    }
+}
+
 fn editable_machine(
     view_mode: ViewMode,
-    machine: &Machine,
+    setup: &Setup,
+    fuel_table: &FuelTable,
     editing_buffer: &mut Option<EditingBuffer>,
     ui: &mut Ui,
 ) -> Option<SetupAction> {
+    let machine = &setup.recipe.machine;
     if let Some(action) = editable_text(
         editing_buffer,
         &machine.name,
         ui,
-        SetupAction::Remove,
+        Some(SetupAction::Remove),
         |name| SetupAction::Rename {
             machine: Machine { name },
         },
@@ -863,13 +1578,19 @@ fn editable_machine(
                     action = Some(SetupAction::InsertProduct {
                         kind,
                         index: None,
-                        product: Product { name: "New".into() },
+                        product: Product {
+                            name: "New".into(),
+                            form: Default::default(),
+                        },
                     });
                 }
             });
             if view_mode != ViewMode::Recipe {
                 ui.menu_button("📜 Add Setup", setup_selector(&mut action));
             }
+            if fuel_table.generator(machine).is_some() {
+                ui.menu_button("⛽ Fuel", fuel_selector(setup, fuel_table, &mut action));
+            }
             ui.separator();
             if ui.button("❌ Remove").clicked() {
                 ui.close_menu();
@@ -881,27 +1602,44 @@ fn editable_machine(
     }
 }
 
+/// Shows a [`Product`] of a [`Setup`] that can be renamed.
+///
+/// If the `processing_chain` is given, the [`Product`] is marked and can be toggled as
+/// explicit input/output of the whole chain.
 fn editable_product(
     product: &Product,
+    processing_chain: Option<&ProcessingChain>,
     editing_buffer: &mut Option<EditingBuffer>,
+    setup_index: usize,
     index: usize,
     kind: ProductKind,
     ui: &mut Ui,
-) -> Option<SetupAction> {
+) -> Option<Action> {
+    let setup_action = |action| Action::Setup {
+        index: setup_index,
+        action,
+    };
+
     if let Some(action) = editable_text(
         editing_buffer,
         &product.name,
         ui,
-        SetupAction::RemoveProduct { kind, index },
+        Some(SetupAction::RemoveProduct { kind, index }),
         |name| SetupAction::RenameProduct {
             kind,
             index,
-            product: Product { name },
+            product: Product {
+                name,
+                form: product.form,
+            },
         },
     ) {
-        action
+        action.map(setup_action)
     } else {
-        let label = ui.label(&product.name);
+        let label = match processing_chain {
+            Some(processing_chain) => product_label(product, processing_chain, ui),
+            None => ui.label(&product.name),
+        };
         if label.clicked() {
             *editing_buffer = Some(EditingBuffer {
                 just_opened: true,
@@ -919,29 +1657,79 @@ fn editable_product(
                 .clicked()
             {
                 ui.close_menu();
-                action = Some(SetupAction::InsertProduct {
+                action = Some(setup_action(SetupAction::InsertProduct {
                     kind,
                     index: Some(index),
-                    product: Product { name: "New".into() },
-                });
+                    product: Product {
+                        name: "New".into(),
+                        form: product.form,
+                    },
+                }));
+            }
+            if let Some(form) = product_form_selector(product.form, ui) {
+                action = Some(setup_action(SetupAction::RenameProduct {
+                    kind,
+                    index,
+                    product: Product {
+                        name: product.name.clone(),
+                        form,
+                    },
+                }));
+            }
+            if let Some(processing_chain) = processing_chain {
+                if let Some(explicit_action) = explicit_io_toggle(product, processing_chain, ui) {
+                    action = Some(explicit_action);
+                }
             }
             ui.separator();
             if ui.button("❌ Remove").clicked() {
                 ui.close_menu();
-                action = Some(SetupAction::RemoveProduct { kind, index });
+                action = Some(setup_action(SetupAction::RemoveProduct { kind, index }));
             }
         });
         action
     }
 }
 
-fn editable_text(
+/// Shows the name of a [`Product`], marking it if it is not balanced.
+fn product_label(product: &Product, processing_chain: &ProcessingChain, ui: &mut Ui) -> Response {
+    if processing_chain.explicit_io().contains(product) {
+        ui.label(RichText::new(format!("📌 {}", product.name)).strong())
+            .on_hover_text("Explicit input/output of the chain, so it is never balanced.")
+    } else if processing_chain.is_balanced(product) {
+        ui.label(&product.name)
+    } else {
+        ui.label(RichText::new(&product.name).italics())
+            .on_hover_text("Only consumed or only produced, so it is not balanced.")
+    }
+}
+
+/// A checkbox to treat the `product` as input/output of the whole chain.
+fn explicit_io_toggle(
+    product: &Product,
+    processing_chain: &ProcessingChain,
+    ui: &mut Ui,
+) -> Option<Action> {
+    let mut explicit = processing_chain.explicit_io().contains(product);
+    ui.checkbox(&mut explicit, "📌 Chain Input/Output")
+        .on_hover_text("Excludes this product from balancing, even if it is consumed and produced.")
+        .changed()
+        .then(|| Action::SetExplicitIo {
+            product: product.clone(),
+            explicit,
+        })
+}
+
+/// Shows a [`TextEdit`] if the `editing_buffer` is set.
+///
+/// Clearing the text results in the `remove_action`, or cancels editing if there is none.
+fn editable_text<A>(
     editing_buffer: &mut Option<EditingBuffer>,
     old_text: &str,
     ui: &mut Ui,
-    remove_action: SetupAction,
-    rename_action: impl FnOnce(String) -> SetupAction,
-) -> Option<Option<SetupAction>> {
+    remove_action: Option<A>,
+    rename_action: impl FnOnce(String) -> A,
+) -> Option<Option<A>> {
     if let Some(EditingBuffer { just_opened, text }) = editing_buffer {
         let mut edit = TextEdit::singleline(text).show(ui);
         if *just_opened {
@@ -958,7 +1746,7 @@ fn editable_text(
             let new_product_name = editing_buffer.take().expect("should be set").text;
             let trimmed_product_name = new_product_name.trim();
             if trimmed_product_name.is_empty() {
-                return Some(Some(remove_action));
+                return Some(remove_action);
             }
 
             if trimmed_product_name != old_text {
@@ -988,28 +1776,158 @@ fn editable_count(
     (new_count != count).then(|| into_action(new_count))
 }
 
+fn editable_chance(
+    chance: Chance,
+    chance_boost: u16,
+    ui: &mut Ui,
+    into_action: impl FnOnce(Chance) -> SetupAction,
+) -> Option<SetupAction> {
+    let mut action = None;
+    let mut basis_points = chance.basis_points();
+    ui.add(
+        basis_points_drag_value(&mut basis_points)
+            .range(Chance::MIN.basis_points()..=Chance::GUARANTEED.basis_points()),
+    )
+    .on_hover_text("Chance of this product being produced per processing cycle.")
+    .context_menu(|ui| {
+        ui.label("Boost per overclocking tier:");
+        let mut new_chance_boost = chance_boost;
+        ui.add(basis_points_drag_value(&mut new_chance_boost).prefix("+"));
+        if new_chance_boost != chance_boost {
+            action = Some(SetupAction::SetChanceBoost {
+                chance_boost: new_chance_boost,
+            });
+        }
+    });
+
+    action.or_else(|| {
+        Chance::new(basis_points)
+            .filter(|new_chance| *new_chance != chance)
+            .map(into_action)
+    })
+}
+
+fn basis_points_drag_value(basis_points: &mut u16) -> DragValue<'_> {
+    DragValue::new(basis_points)
+        .custom_parser(|text| text.parse::<f64>().ok().map(|value| value * 100.0))
+        .custom_formatter(|value, _| (value / 100.0).to_string())
+        .suffix("%")
+}
+
 fn editable_amount(
-    count: NonZeroU64,
+    product_count: &ProductCount,
     amount: &Rational,
     ui: &mut Ui,
     into_action: impl FnOnce(NonZeroU64) -> SetupAction,
 ) -> Option<SetupAction> {
     let mut action = None;
-    let mut options = ToSciOptions::default();
-    options.set_scale(2);
-    ui.label(format!("{}/s", amount.to_sci_with_options(options)))
+    let form = product_count.product.form;
+    amount_per_sec(ui, form, amount)
         .on_hover_ui(|ui| {
-            ui.set_max_width(ui.spacing().tooltip_width);
-            let (products, sec) = amount.numerator_and_denominator_ref();
-            ui.label(format!("{products} 📦 / {sec} s"));
+            amount_hover(ui, form, amount);
             ui.label("Right-click to edit recipe count.");
         })
         .context_menu(|ui| {
-            action = editable_count(count, ui, into_action);
+            action = editable_count(product_count.count, ui, into_action);
         });
     action
 }
 
+/// Shows the exact fraction of an `amount` per second, as well as other common units.
+fn amount_hover(ui: &mut Ui, form: ProductForm, amount: &Rational) {
+    ui.set_max_width(ui.spacing().tooltip_width);
+    let (products, sec) = amount.numerator_and_denominator_ref();
+    ui.label(format!("{products}{} / {sec} s", form.unit()));
+
+    let mut options = ToSciOptions::default();
+    options.set_scale(2);
+    match form {
+        ProductForm::Item => {
+            let stacks_per_min = amount * Rational::from_unsigneds(60u8, 64);
+            ui.label(format!(
+                "{} stacks/min",
+                stacks_per_min.to_sci_with_options(options)
+            ));
+        }
+        ProductForm::Fluid => {
+            let buckets_per_sec = amount / Rational::from(1000);
+            ui.label(format!(
+                "{} buckets/s",
+                buckets_per_sec.to_sci_with_options(options)
+            ));
+        }
+        ProductForm::Virtual => {}
+    }
+}
+
+fn amount_per_sec(ui: &mut Ui, form: ProductForm, amount: &Rational) -> Response {
+    let mut options = ToSciOptions::default();
+    options.set_scale(2);
+    ui.label(format!(
+        "{}{}/s",
+        amount.to_sci_with_options(options),
+        form.unit()
+    ))
+}
+
+fn product_form_selector(form: ProductForm, ui: &mut Ui) -> Option<ProductForm> {
+    let mut new_form = None;
+    ui.menu_button(format!("🏷 {}", form.name()), |ui| {
+        for option in [ProductForm::Item, ProductForm::Fluid, ProductForm::Virtual] {
+            if ui.radio(form == option, option.name()).clicked() {
+                ui.close_menu();
+                new_form = (form != option).then_some(option);
+            }
+        }
+    });
+    new_form
+}
+
+/// Edits a `rate` per second, keeping it as the simplest fraction that matches the input.
+fn editable_rate(rate: &mut Rational, form: ProductForm, ui: &mut Ui) -> Response {
+    let mut value = f64::rounding_from(&*rate, RoundingMode::Nearest).0;
+    let response = ui.add(DragValue::new(&mut value).suffix(format!("{}/s", form.unit())));
+    if response.changed() {
+        if let Ok(new_rate) = Rational::try_from_float_simplest(value) {
+            *rate = new_rate;
+        }
+    }
+    response
+}
+
+fn editable_weight(
+    processing_chain: &ProcessingChain,
+    setup_index: usize,
+    ui: &mut Ui,
+) -> Option<SetupAction> {
+    let weight = processing_chain.setups()[setup_index].weight;
+    let mut new_weight = weight.0;
+    ui.add(DragValue::new(&mut new_weight).prefix("×"))
+        .on_hover_ui(|ui| {
+            ui.set_max_width(ui.spacing().tooltip_width);
+            let shares = processing_chain.weight_shares(setup_index);
+            if shares.is_empty() {
+                ui.label("No products are shared with other machines.");
+            }
+            for (product, share) in shares {
+                let percent = &share * Rational::from(100);
+                let mut options = ToSciOptions::default();
+                options.set_scale(2);
+                let direction = if share < 0 { "consumer" } else { "producer" };
+                ui.label(format!(
+                    "{}: {} ({}%) as {direction}",
+                    product.name,
+                    share.abs(),
+                    percent.abs().to_sci_with_options(options)
+                ));
+            }
+            ui.label("Shares follow the current speeds of all machines.");
+        });
+    (new_weight != weight.0).then_some(SetupAction::SetWeight {
+        weight: Weight(new_weight),
+    })
+}
+
 fn editable_time(recipe: &Recipe, ui: &mut Ui) -> Option<SetupAction> {
     let mut ticks = recipe.ticks;
     ui.add(
@@ -1026,47 +1944,231 @@ enum TotalTableCellContent {
     Header,
     /// Can be modified, which updates the name in _all_ [`Setup`]s.
     Product(Product),
-    ProductAmount(Box<Rational>),
+    ProductAmount {
+        form: ProductForm,
+        amount: Box<Rational>,
+    },
     EuPerTick(Box<Rational>),
 }
 
 impl TotalTableCellContent {
-    fn show(&self, ui: &mut Ui) {
+    fn show(
+        &self,
+        processing_chain: &ProcessingChain,
+        editing_buffer: &mut Option<EditingBuffer>,
+        ui: &mut Ui,
+    ) -> Option<Action> {
         match self {
             Self::Header => {
                 ui.label("Total");
             }
             Self::Product(product) => {
-                ui.label(&product.name);
+                if let Some(action) =
+                    editable_text(editing_buffer, &product.name, ui, None, |name| {
+                        Action::ReplaceProduct {
+                            old: product.clone(),
+                            new: Product {
+                                name,
+                                form: product.form,
+                            },
+                        }
+                    })
+                {
+                    return action;
+                }
+
+                let label = product_label(product, processing_chain, ui).on_hover_text(
+                    "Click to rename in all setups.\n\
+                    Renaming to an existing product merges both.",
+                );
+                if label.clicked() {
+                    *editing_buffer = Some(EditingBuffer {
+                        just_opened: true,
+                        text: product.name.clone(),
+                    });
+                }
+
+                let mut action = None;
+                label.context_menu(|ui| {
+                    if let Some(explicit_action) = explicit_io_toggle(product, processing_chain, ui)
+                    {
+                        action = Some(explicit_action);
+                    }
+                    if let Some(linear_program) = processing_chain.linear_program() {
+                        let net_rate = linear_program.constraint(product);
+                        ui.menu_button(
+                            format!("⚖ {}", net_rate.as_ref().map_or("Default", NetRate::name)),
+                            |ui| {
+                                let default_text = if processing_chain.is_balanced(product) {
+                                    "Default (Zero)"
+                                } else {
+                                    "Default (Unconstrained)"
+                                };
+                                let mut new_net_rate = net_rate.clone();
+                                ui.selectable_value(&mut new_net_rate, None, default_text);
+                                ui.separator();
+                                for option in [NetRate::Zero, NetRate::Surplus, NetRate::Deficit] {
+                                    let text = option.name();
+                                    ui.selectable_value(&mut new_net_rate, Some(option), text);
+                                }
+                                let mut close_menu = new_net_rate != net_rate;
+                                match &mut new_net_rate {
+                                    Some(NetRate::Bounded { min, max }) => {
+                                        ui.selectable_label(true, "Bounded").on_hover_text(
+                                            "The net rate must stay within both values.",
+                                        );
+                                        ui.horizontal(|ui| {
+                                            editable_rate(min, product.form, ui)
+                                                .on_hover_text("Minimum net rate");
+                                            ui.label("to");
+                                            editable_rate(max, product.form, ui)
+                                                .on_hover_text("Maximum net rate");
+                                        });
+                                    }
+                                    _ => {
+                                        if ui.selectable_label(false, "Bounded").clicked() {
+                                            new_net_rate = Some(NetRate::Bounded {
+                                                min: Rational::ZERO,
+                                                max: Rational::ZERO,
+                                            });
+                                            close_menu = false;
+                                        }
+                                    }
+                                }
+                                if new_net_rate != net_rate {
+                                    if close_menu {
+                                        ui.close_menu();
+                                    }
+                                    action = Some(Action::SetNetRate {
+                                        product: product.clone(),
+                                        net_rate: new_net_rate,
+                                    });
+                                }
+                            },
+                        );
+                    }
+                    if let Some(form) = product_form_selector(product.form, ui) {
+                        action = Some(Action::ReplaceProduct {
+                            old: product.clone(),
+                            new: Product {
+                                name: product.name.clone(),
+                                form,
+                            },
+                        });
+                    }
+                });
+                return action;
             }
-            Self::ProductAmount(amount) => {
-                let mut options = ToSciOptions::default();
-                options.set_scale(2);
-                ui.label(format!("{}/s", amount.to_sci_with_options(options)));
-                // TODO: on_hover like for editable_amount
+            Self::ProductAmount { form, amount } => {
+                amount_per_sec(ui, *form, amount).on_hover_ui(|ui| amount_hover(ui, *form, amount));
+            }
+            Self::EuPerTick(eu) => {
+                let mut action = None;
+                eu_per_tick(ui, eu).context_menu(|ui| {
+                    let mut balance_eu = processing_chain.balance_eu();
+                    if ui
+                        .checkbox(&mut balance_eu, "Balance EU")
+                        .on_hover_text(
+                            "Throttles generators and consumers so that the chain is power-neutral.",
+                        )
+                        .changed()
+                    {
+                        action = Some(Action::SetBalanceEu { balance_eu });
+                    }
+                });
+                return action;
             }
-            Self::EuPerTick(eu) => eu_per_tick(ui, eu),
         }
+        None
     }
 }
 
 enum Action {
-    Setup { index: usize, action: SetupAction },
-    ReplaceProduct { old: Product, new: Product },
+    Setup {
+        index: usize,
+        action: SetupAction,
+    },
+    ReplaceProduct {
+        old: Product,
+        new: Product,
+    },
+    SetBalanceEu {
+        balance_eu: bool,
+    },
+    SetExplicitIo {
+        product: Product,
+        explicit: bool,
+    },
+    SetTarget {
+        target: Option<Target>,
+    },
+    SetMachines {
+        machines: Vec<Option<Machines>>,
+    },
+    SetObjective {
+        objective: Option<Objective>,
+    },
+    SetNetRate {
+        product: Product,
+        net_rate: Option<NetRate>,
+    },
 }
 
 impl Action {
     /// Performs the action on the given `processing_chain`.
     ///
     /// Returns which cached [`ProcessingChainTable::rows`] need to be invalidated.
-    fn execute(self, processing_chain: &mut ProcessingChain) -> EnumSet<ViewMode> {
-        match self {
-            Self::Setup { index, action } => action.apply(processing_chain, index),
+    fn execute(
+        self,
+        processing_chain: &mut ProcessingChain,
+        fuel_table: &FuelTable,
+    ) -> Result<EnumSet<ViewMode>, FuelError> {
+        Ok(match self {
+            Self::Setup { index, action } => action.apply(processing_chain, fuel_table, index)?,
             Self::ReplaceProduct { old, new } => {
                 processing_chain.replace_product(&old, new);
                 ViewMode::ALL
             }
-        }
+            Self::SetBalanceEu { balance_eu } => {
+                processing_chain.set_balance_eu(balance_eu);
+                ViewMode::CALCULATED
+            }
+            Self::SetExplicitIo { product, explicit } => {
+                let explicit_io = processing_chain.explicit_io_mut();
+                if explicit {
+                    explicit_io.insert(product);
+                } else {
+                    explicit_io.remove(&product);
+                }
+                ViewMode::CALCULATED
+            }
+            Self::SetTarget { target } => {
+                processing_chain.set_target(target);
+                enum_set![ViewMode::Target]
+            }
+            Self::SetObjective { objective } => {
+                let linear_program = processing_chain.linear_program_mut();
+                match objective {
+                    Some(objective) => linear_program.get_or_insert_default().objective = objective,
+                    None => *linear_program = None,
+                }
+                ViewMode::CALCULATED
+            }
+            Self::SetNetRate { product, net_rate } => {
+                if let Some(linear_program) = processing_chain.linear_program_mut() {
+                    linear_program.set_constraint(&product, net_rate);
+                }
+                ViewMode::CALCULATED
+            }
+            Self::SetMachines { machines } => {
+                for (setup, machines) in processing_chain.setups_mut().iter_mut().zip_eq(machines) {
+                    if let Some(machines) = machines {
+                        setup.machines = machines;
+                    }
+                }
+                ViewMode::CALCULATED
+            }
+        })
     }
 }
 
@@ -1088,6 +2190,10 @@ enum SetupAction {
     Rename {
         machine: Machine,
     },
+    /// Replaces the recipe with the one of a generator burning the `fuel`.
+    SetFuel {
+        fuel: Product,
+    },
 
     InsertProduct {
         kind: ProductKind,
@@ -1118,6 +2224,10 @@ enum SetupAction {
         index: usize,
         count: NonZeroU64,
     },
+    SetProducedChance {
+        index: usize,
+        chance: Chance,
+    },
 
     SetTime {
         ticks: NonZeroU64,
@@ -1125,23 +2235,60 @@ enum SetupAction {
     SetEuPerTick {
         eu_per_tick: i64,
     },
+    SetChanceBoost {
+        chance_boost: u16,
+    },
+    SetOverclocking {
+        overclocking: Overclocking,
+    },
+    SetSubtickParallels {
+        subtick_parallels: bool,
+    },
+    SetHeat {
+        heat: Option<NonZeroU64>,
+    },
+    SetAmperage {
+        amperage: NonZeroU64,
+    },
 
     InsertMachine {
         clocked_machine: Option<ClockedMachine>,
     },
+    InsertSteamMachine {
+        tier: SteamTier,
+    },
     SetMachineCount {
         clocked_machine: Option<ClockedMachine>,
         count: u64,
     },
+    SetSteamMachineCount {
+        tier: SteamTier,
+        count: u64,
+    },
+    SetParallels {
+        clocked_machine: ClockedMachine,
+        parallels: NonZeroU64,
+    },
+    SetCoil {
+        coil: Option<Coil>,
+    },
+    SetMachineAmperage {
+        amperage: NonZeroU64,
+    },
+
+    SetWeight {
+        weight: Weight,
+    },
 }
 
 impl SetupAction {
     fn apply(
         self,
         processing_chain: &mut ProcessingChain,
+        fuel_table: &FuelTable,
         setup_index: usize,
-    ) -> EnumSet<ViewMode> {
-        match self {
+    ) -> Result<EnumSet<ViewMode>, FuelError> {
+        Ok(match self {
             Self::Insert { machine } => {
                 processing_chain
                     .setups_mut()
@@ -1160,6 +2307,15 @@ impl SetupAction {
                 *processing_chain.machine_mut(setup_index) = machine;
                 ViewMode::NONE
             }
+            Self::SetWeight { weight } => {
+                processing_chain.set_weight(setup_index, weight);
+                enum_set![ViewMode::Speed | ViewMode::Target]
+            }
+            Self::SetFuel { fuel } => {
+                let setup = &mut processing_chain.setups_mut()[setup_index];
+                setup.recipe = fuel_table.recipe(&setup.recipe.machine, &fuel)?;
+                ViewMode::ALL
+            }
             Self::InsertProduct {
                 kind,
                 index,
@@ -1176,18 +2332,12 @@ impl SetupAction {
                     ProductKind::Consumed => insert_or_append(
                         &mut processing_chain.setups_mut()[setup_index].recipe.consumed,
                         index,
-                        ProductCount {
-                            product,
-                            count: NonZeroU64::MIN,
-                        },
+                        ProductCount::new(product),
                     ),
                     ProductKind::Produced => insert_or_append(
                         &mut processing_chain.setups_mut()[setup_index].recipe.produced,
                         index,
-                        ProductCount {
-                            product,
-                            count: NonZeroU64::MIN,
-                        },
+                        ProductCount::new(product),
                     ),
                 }
                 ViewMode::ALL
@@ -1296,6 +2446,10 @@ impl SetupAction {
                 processing_chain.setups_mut()[setup_index].recipe.consumed[index].count = count;
                 ViewMode::CALCULATED
             }
+            Self::SetProducedChance { index, chance } => {
+                processing_chain.setups_mut()[setup_index].recipe.produced[index].chance = chance;
+                ViewMode::CALCULATED
+            }
             Self::SetTime { ticks } => {
                 processing_chain.setups_mut()[setup_index].recipe.ticks = ticks;
                 ViewMode::CALCULATED
@@ -1306,6 +2460,32 @@ impl SetupAction {
                     .eu_per_tick = eu_per_tick;
                 ViewMode::CALCULATED
             }
+            Self::SetChanceBoost { chance_boost } => {
+                processing_chain.setups_mut()[setup_index]
+                    .recipe
+                    .chance_boost = chance_boost;
+                ViewMode::CALCULATED
+            }
+            Self::SetOverclocking { overclocking } => {
+                processing_chain.setups_mut()[setup_index]
+                    .recipe
+                    .overclocking = overclocking;
+                ViewMode::CALCULATED
+            }
+            Self::SetSubtickParallels { subtick_parallels } => {
+                processing_chain.setups_mut()[setup_index]
+                    .recipe
+                    .subtick_parallels = subtick_parallels;
+                ViewMode::CALCULATED
+            }
+            Self::SetHeat { heat } => {
+                processing_chain.setups_mut()[setup_index].recipe.heat = heat;
+                ViewMode::CALCULATED
+            }
+            Self::SetAmperage { amperage } => {
+                processing_chain.setups_mut()[setup_index].recipe.amperage = amperage;
+                ViewMode::CALCULATED
+            }
             Self::InsertMachine { clocked_machine } => {
                 let machines = &mut processing_chain.setups_mut()[setup_index].machines;
                 if let Some(clocked_machine) = clocked_machine {
@@ -1320,6 +2500,16 @@ impl SetupAction {
                 }
                 ViewMode::ALL
             }
+            Self::InsertSteamMachine { tier } => {
+                processing_chain.setups_mut()[setup_index]
+                    .machines
+                    .into_steam()
+                    .steam
+                    .entry(tier)
+                    .and_modify(|count| *count = count.saturating_add(1))
+                    .or_insert(NonZeroU64::MIN);
+                ViewMode::ALL
+            }
             Self::SetMachineCount {
                 clocked_machine,
                 count,
@@ -1341,7 +2531,51 @@ impl SetupAction {
                 }
                 ViewMode::CALCULATED
             }
-        }
+            Self::SetSteamMachineCount { tier, count } => {
+                let steam = &mut processing_chain.setups_mut()[setup_index]
+                    .machines
+                    .into_steam()
+                    .steam;
+                if let Some(count) = NonZeroU64::new(count) {
+                    steam.insert(tier, count);
+                } else {
+                    steam.remove(&tier);
+                }
+                ViewMode::CALCULATED
+            }
+            Self::SetParallels {
+                clocked_machine,
+                parallels,
+            } => {
+                let machines = &mut processing_chain.setups_mut()[setup_index]
+                    .machines
+                    .into_clocked()
+                    .machines;
+                if let Some(count) = machines.remove(&clocked_machine) {
+                    machines
+                        .entry(clocked_machine.with_parallels(parallels))
+                        .and_modify(|existing_count| {
+                            *existing_count = existing_count.saturating_add(count.get())
+                        })
+                        .or_insert(count);
+                }
+                ViewMode::CALCULATED
+            }
+            Self::SetCoil { coil } => {
+                processing_chain.setups_mut()[setup_index]
+                    .machines
+                    .into_clocked()
+                    .coil = coil;
+                ViewMode::CALCULATED
+            }
+            Self::SetMachineAmperage { amperage } => {
+                processing_chain.setups_mut()[setup_index]
+                    .machines
+                    .into_clocked()
+                    .amperage = amperage;
+                ViewMode::CALCULATED
+            }
+        })
     }
 }
 
@@ -1362,3 +2596,24 @@ struct EditingBuffer {
     just_opened: bool,
     text: String,
 }
+
+/// Persists [`ProcessingChain`]s as JSON strings, since eframe's RON storage cannot represent
+/// untagged and flattened fields.
+pub(super) mod processing_chain_json {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::model::processing_chain::ProcessingChain;
+
+    pub fn serialize<S: Serializer>(
+        value: &ProcessingChain,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_json())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ProcessingChain, D::Error> {
+        serde_json::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
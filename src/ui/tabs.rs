@@ -1,157 +1,384 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::{read_to_string, write};
+#[cfg(target_arch = "wasm32")]
+use std::path::Path;
 use std::{
-    collections::BTreeMap,
-    fs::{read_to_string, write},
-    io,
-    path::{Path, PathBuf},
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::bail;
 use anyhow::Result;
-use egui::{Color32, Id, Ui, WidgetText};
-use egui_dock::{NodeIndex, SurfaceIndex, TabViewer};
+#[cfg(target_arch = "wasm32")]
+use eframe::wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use egui::{Align2, Color32, Context, Id, Ui, WidgetText, Window};
+use egui_dock::{DockState, NodeIndex, SurfaceIndex, TabViewer};
+#[cfg(target_arch = "wasm32")]
+use rfd::AsyncFileDialog;
+#[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{processing::ProcessingChain, Notification};
+use super::app::Notification;
+use super::processing_chain_table::{processing_chain_json, ProcessingChainTable};
+use crate::model::{fuel::FuelTable, processing_chain::ProcessingChain};
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 enum UnsavedChanges {
-    Dialog,
-    Discard,
+    /// Asks the user whether the changes should be discarded.
+    Dialog(ProcessingChainTab),
+    /// Forces the [`ProcessingChainTab`] to close without saving.
+    Discard(ProcessingChainTab),
 }
 
-#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Tabs {
-    new_processing_chains: BTreeMap<Uuid, ProcessingChain>,
+    new_processing_chains: BTreeMap<Uuid, ProcessingChainTable>,
     processing_chains: BTreeMap<PathBuf, SavedProcessingChain>,
+    /// The bundled `fuels.json` unless another one is opened; see [`Self::set_fuel_table`].
+    #[serde(skip)]
+    fuel_table: FuelTable,
+    #[serde(skip)]
     unsaved_changes: Option<UnsavedChanges>,
+    #[serde(skip)]
     new_tabs: Vec<(SurfaceIndex, NodeIndex)>,
+    #[serde(skip)]
+    notifications: Vec<Notification>,
 }
 
 impl Tabs {
-    fn get_processing_chain_mut(&mut self, tab: &ProcessingChainTab) -> &mut ProcessingChain {
-        match tab {
-            ProcessingChainTab::New { id } => self.new_processing_chains.entry(*id).or_default(),
-            ProcessingChainTab::Path(path) => {
-                &mut self
-                    .processing_chains
-                    .get_mut(path)
-                    .expect("processing chain should be loaded")
-                    .current
-            }
+    pub fn new(fuel_table: FuelTable) -> Self {
+        Self {
+            new_processing_chains: Default::default(),
+            processing_chains: Default::default(),
+            fuel_table,
+            unsaved_changes: None,
+            new_tabs: Default::default(),
+            notifications: Default::default(),
         }
     }
 
-    fn unload_processing_chain(&mut self, tab: &ProcessingChainTab) {
+    /// Replaces the [`FuelTable`] used by all [`Tab`]s.
+    pub fn set_fuel_table(&mut self, fuel_table: FuelTable) {
+        self.fuel_table = fuel_table;
+    }
+
+    /// Closes [`Tab`]s without a loaded [`ProcessingChain`] and unloads [`ProcessingChain`]s
+    /// without a [`Tab`], e.g. after restoring both from storage.
+    pub fn retain_open(&mut self, dock_state: &mut DockState<Tab>) {
+        dock_state.retain_tabs(|Tab::ProcessingChain(tab)| match tab {
+            ProcessingChainTab::New { id } => self.new_processing_chains.contains_key(id),
+            ProcessingChainTab::Path(path) => self.processing_chains.contains_key(path),
+        });
+
+        let open_tabs = dock_state
+            .iter_all_tabs()
+            .map(|(_, Tab::ProcessingChain(tab))| tab)
+            .collect::<BTreeSet<_>>();
+        self.new_processing_chains
+            .retain(|id, _| open_tabs.contains(&ProcessingChainTab::New { id: *id }));
+        self.processing_chains
+            .retain(|path, _| open_tabs.contains(&ProcessingChainTab::Path(path.clone())));
+    }
+
+    /// Opens the `processing_chain` as a [`ProcessingChainTab::New`].
+    pub fn insert_new_processing_chain(&mut self, processing_chain: ProcessingChain) -> Tab {
+        let id = Uuid::new_v4();
+        self.new_processing_chains
+            .insert(id, ProcessingChainTable::new(processing_chain));
+        Tab::ProcessingChain(ProcessingChainTab::New { id })
+    }
+
+    /// Returns the nodes at which new [`Tab`]s were requested using the add button.
+    pub fn take_new_tabs(&mut self) -> Vec<(SurfaceIndex, NodeIndex)> {
+        std::mem::take(&mut self.new_tabs)
+    }
+
+    /// Returns the [`Notification`]s raised while showing the [`Tab`]s.
+    pub fn take_notifications(&mut self) -> Vec<Notification> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Shows a dialog if a [`Tab`] with unsaved changes is about to be closed.
+    pub fn show_unsaved_changes_dialog(&mut self, ctx: &Context) {
+        let Some(UnsavedChanges::Dialog(tab)) = &self.unsaved_changes else {
+            return;
+        };
+
+        let mut unsaved_changes = self.unsaved_changes.clone();
+        Window::new("Unsaved Changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "\"{}\" has unsaved changes. Discard them?",
+                    tab.name()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Discard").clicked() {
+                        unsaved_changes = Some(UnsavedChanges::Discard(tab.clone()));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        unsaved_changes = None;
+                    }
+                });
+            });
+        self.unsaved_changes = unsaved_changes;
+    }
+
+    /// Loads the file at `path` unless it is already open.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_processing_chain_tab(&mut self, path: PathBuf) -> Result<Tab, Notification> {
+        if !self.processing_chains.contains_key(&path) {
+            read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| self.insert_processing_chain(path.clone(), &json))
+                .map_err(|error| Notification::Error(format!("{}: {error}", path.display())))?;
+        }
+        Ok(Tab::ProcessingChain(ProcessingChainTab::Path(path)))
+    }
+
+    /// Loads the content of a file picked in the browser into a new [`Tab`].
+    ///
+    /// The browser only provides the file name, so opening a file with the same name as an
+    /// already open one gets a numbered name instead of replacing it.
+    #[cfg(target_arch = "wasm32")]
+    pub fn load_processing_chain_bytes(
+        &mut self,
+        file_name: String,
+        bytes: &[u8],
+    ) -> Result<Tab, Notification> {
+        let path = self.free_path(&file_name);
+        std::str::from_utf8(bytes)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| self.insert_processing_chain(path.clone(), json))
+            .map_err(|error| Notification::Error(format!("{file_name}: {error}")))?;
+        Ok(Tab::ProcessingChain(ProcessingChainTab::Path(path)))
+    }
+
+    /// Saves the `tab` to its file, asking for a path if it does not have one yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_processing_chain_tab(
+        &mut self,
+        tab: &mut ProcessingChainTab,
+    ) -> Option<Notification> {
         match tab {
-            ProcessingChainTab::New { id } => {
-                self.new_processing_chains.remove(id);
-            }
+            ProcessingChainTab::New { .. } => self.save_processing_chain_tab_as(tab),
             ProcessingChainTab::Path(path) => {
-                self.processing_chains
-                    .remove(path)
-                    .expect("processing chain should exist");
+                let path = path.clone();
+                self.save_processing_chain_as(tab, path.clone())
+                    .err()
+                    .map(|error| Notification::Error(format!("{}: {error}", path.display())))
             }
         }
     }
 
-    fn load_processing_chain(&mut self, path: PathBuf) -> Result<()> {
-        let content = read_to_string(&path)?;
-        let processing_chain = serde_json::from_str(&content)?;
-        self.processing_chains.insert(path, processing_chain);
+    /// Asks for a path to save the `tab` to, which then refers to the new file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_processing_chain_tab_as(
+        &mut self,
+        tab: &mut ProcessingChainTab,
+    ) -> Option<Notification> {
+        let path = processing_chain_file_dialog()
+            .set_file_name(format!("{}.json", tab.name().trim_end_matches(".json")))
+            .save_file()?;
+        self.save_processing_chain_as(tab, path.clone())
+            .err()
+            .map(|error| Notification::Error(format!("{}: {error}", path.display())))
+    }
 
-        Ok(())
+    /// Downloads the `tab` as a file, since the browser does not allow writing to disk.
+    ///
+    /// Unsaved [`ProcessingChainTab::New`]s are named after the first free file name. The `tab`
+    /// keeps its unsaved changes, since the download might still be cancelled.
+    #[cfg(target_arch = "wasm32")]
+    pub fn download_processing_chain_tab(
+        &mut self,
+        tab: &ProcessingChainTab,
+    ) -> Option<Notification> {
+        let path = match tab {
+            ProcessingChainTab::New { .. } => self.free_path("processing_chain.json"),
+            ProcessingChainTab::Path(path) => path.clone(),
+        };
+
+        let json = self
+            .get_processing_chain_mut(tab)
+            .processing_chain()
+            .to_json();
+        if let Err(error) = download(&path.to_string_lossy(), &json) {
+            return Some(Notification::Error(format!(
+                "{}: {error:?}",
+                path.display()
+            )));
+        }
+
+        None
+    }
+
+    /// The first of `file_name`, `file_name (2)`, etc. that is not open in any [`Tab`] yet.
+    #[cfg(target_arch = "wasm32")]
+    fn free_path(&self, file_name: &str) -> PathBuf {
+        let path = Path::new(file_name);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+        (1..)
+            .map(|index| match index {
+                1 => PathBuf::from(file_name),
+                _ => PathBuf::from(format!("{stem} ({index}){extension}")),
+            })
+            .find(|path| !self.processing_chains.contains_key(path))
+            .expect("there should be a free file name")
     }
 
-    fn save_new_processing_chain(&mut self, id: Uuid, path: PathBuf) -> io::Result<()> {
-        write(
-            &path,
-            self.new_processing_chains
-                .get(&id)
-                .map(|processing_chain| processing_chain.to_json())
-                .unwrap_or_else(|| ProcessingChain::default().to_json()),
-        )?;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_processing_chain_as(
+        &mut self,
+        tab: &mut ProcessingChainTab,
+        path: PathBuf,
+    ) -> Result<()> {
+        let same_path = matches!(tab, ProcessingChainTab::Path(old_path) if *old_path == path);
+        if !same_path && self.processing_chains.contains_key(&path) {
+            bail!("already opened in another tab");
+        }
 
-        let processing_chain = self.new_processing_chains.remove(&id).unwrap_or_default();
+        let json = self
+            .get_processing_chain_mut(tab)
+            .processing_chain()
+            .to_json();
+        write(&path, json)?;
 
+        self.set_saved(tab, path);
+        Ok(())
+    }
+
+    fn insert_processing_chain(&mut self, path: PathBuf, json: &str) -> Result<()> {
+        let processing_chain: ProcessingChain = serde_json::from_str(json)?;
         self.processing_chains.insert(
             path,
             SavedProcessingChain {
                 saved: processing_chain.clone(),
-                current: processing_chain,
+                current: ProcessingChainTable::new(processing_chain),
             },
         );
 
         Ok(())
     }
 
-    fn save_processing_chain(&mut self, path: &Path) -> io::Result<()> {
-        let processing_chain = self
-            .processing_chains
-            .get_mut(path)
-            .expect("processing chain should exist");
-
-        write(path, processing_chain.current.to_json())?;
-        processing_chain.saved = processing_chain.current.clone();
-
-        Ok(())
+    /// Marks the current state of the `tab` as saved to `path`, which the `tab` refers to from
+    /// now on.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_saved(&mut self, tab: &mut ProcessingChainTab, path: PathBuf) {
+        let current = match tab {
+            ProcessingChainTab::New { id } => {
+                self.new_processing_chains.remove(id).unwrap_or_default()
+            }
+            ProcessingChainTab::Path(old_path) => {
+                self.processing_chains
+                    .remove(old_path)
+                    .expect("processing chain should exist")
+                    .current
+            }
+        };
+        self.processing_chains.insert(
+            path.clone(),
+            SavedProcessingChain {
+                saved: current.processing_chain().clone(),
+                current,
+            },
+        );
+        *tab = ProcessingChainTab::Path(path);
     }
 
-    fn load_processing_chain_tab(&mut self, path: PathBuf) -> Result<Tab, Notification> {
-        if let Err(error) = self.load_processing_chain(path.clone()) {
-            Err(Notification::Error(error.to_string()))
-        } else {
-            Ok(Tab::ProcessingChain(ProcessingChainTab::Path(path)))
+    fn get_processing_chain_mut(&mut self, tab: &ProcessingChainTab) -> &mut ProcessingChainTable {
+        match tab {
+            ProcessingChainTab::New { id } => self.new_processing_chains.entry(*id).or_default(),
+            ProcessingChainTab::Path(path) => {
+                &mut self
+                    .processing_chains
+                    .get_mut(path)
+                    .expect("processing chain should be loaded")
+                    .current
+            }
         }
     }
 
-    fn save_processing_chain_tab(&mut self, tab: &ProcessingChainTab) -> Option<Notification> {
+    /// Whether the [`ProcessingChain`] of the `tab` would be lost when closing it.
+    fn changed(&self, tab: &ProcessingChainTab) -> bool {
         match tab {
-            ProcessingChainTab::New { .. } => self.save_processing_chain_tab_as(tab),
+            ProcessingChainTab::New { id } => self
+                .new_processing_chains
+                .get(id)
+                .is_some_and(|processing_chain| !processing_chain.processing_chain().is_empty()),
             ProcessingChainTab::Path(path) => self
-                .save_processing_chain(path)
-                .err()
-                .map(|error| Notification::Error(error.to_string())),
+                .processing_chains
+                .get(path)
+                .is_some_and(SavedProcessingChain::changed),
         }
     }
 
-    fn save_processing_chain_tab_as(&mut self, tab: &ProcessingChainTab) -> Option<Notification> {
-        processing_chain_file_dialog()
-            .save_file()
-            .and_then(|path| match tab {
-                ProcessingChainTab::New { id } => self
-                    .save_new_processing_chain(*id, path)
-                    .err()
-                    .map(|error| Notification::Error(error.to_string())),
-                ProcessingChainTab::Path(path) => self
-                    .save_processing_chain(path)
-                    .err()
-                    .map(|error| Notification::Error(error.to_string())),
-            })
+    fn unload_processing_chain(&mut self, tab: &ProcessingChainTab) {
+        match tab {
+            ProcessingChainTab::New { id } => {
+                self.new_processing_chains.remove(id);
+            }
+            ProcessingChainTab::Path(path) => {
+                self.processing_chains
+                    .remove(path)
+                    .expect("processing chain should exist");
+            }
+        }
     }
 }
 
 impl TabViewer for Tabs {
     type Tab = Tab;
 
-    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+    fn title(&mut self, tab: &mut Self::Tab) -> WidgetText {
         match tab {
-            Tab::Config => WidgetText::from("Config").strong(),
-            Tab::ProcessingChain(tab) => match tab {
-                ProcessingChainTab::New { .. } => WidgetText::from("New").strong(),
-                ProcessingChainTab::Path(path) => {
-                    if let Some(file_name) = path.file_name() {
-                        file_name.to_string_lossy().into()
-                    } else {
-                        WidgetText::from("Invalid Filename").color(Color32::RED)
+            Tab::ProcessingChain(tab) => {
+                let mut title = match tab {
+                    ProcessingChainTab::New { .. } => WidgetText::from("New").strong(),
+                    ProcessingChainTab::Path(path) => {
+                        if let Some(file_name) = path.file_name() {
+                            file_name.to_string_lossy().into()
+                        } else {
+                            WidgetText::from("Invalid Filename").color(Color32::RED)
+                        }
                     }
+                };
+                if self.changed(tab) {
+                    title = title.italics();
                 }
-            },
+                title
+            }
         }
     }
 
     fn ui(&mut self, ui: &mut Ui, tab: &mut Self::Tab) {
-        ui.label(format!("{tab:#?}"));
+        match tab {
+            Tab::ProcessingChain(tab) => {
+                let processing_chain = match tab {
+                    ProcessingChainTab::New { id } => {
+                        self.new_processing_chains.entry(*id).or_default()
+                    }
+                    ProcessingChainTab::Path(path) => {
+                        &mut self
+                            .processing_chains
+                            .get_mut(path)
+                            .expect("processing chain should be loaded")
+                            .current
+                    }
+                };
+                self.notifications
+                    .extend(processing_chain.show(&self.fuel_table, ui));
+            }
+        }
     }
 
     fn id(&mut self, tab: &mut Self::Tab) -> Id {
@@ -159,33 +386,28 @@ impl TabViewer for Tabs {
     }
 
     fn on_close(&mut self, tab: &mut Self::Tab) -> bool {
-        let can_close = match tab {
-            Tab::Config => true,
-            Tab::ProcessingChain(tab) => match tab {
-                ProcessingChainTab::New { id } => self
-                    .new_processing_chains
-                    .get(id)
-                    .is_none_or(|processing_chain| processing_chain.is_empty()),
-                ProcessingChainTab::Path(path) => !&self.processing_chains[path].changed(),
-            },
-        };
+        let Tab::ProcessingChain(tab) = tab;
+        let can_close = !self.changed(tab);
 
-        if !can_close {
-            self.unsaved_changes = Some(UnsavedChanges::Dialog);
-        } else if let Tab::ProcessingChain(tab) = tab {
+        if can_close {
             self.unload_processing_chain(tab);
+        } else {
+            self.unsaved_changes = Some(UnsavedChanges::Dialog(tab.clone()));
         }
 
         can_close
     }
 
     fn force_close(&mut self, tab: &mut Self::Tab) -> bool {
-        let close = matches!(self.unsaved_changes, Some(UnsavedChanges::Discard));
+        let Tab::ProcessingChain(tab) = tab;
+        let close = matches!(
+            &self.unsaved_changes,
+            Some(UnsavedChanges::Discard(discarded)) if discarded == tab
+        );
 
         if close {
-            if let Tab::ProcessingChain(tab) = tab {
-                self.unload_processing_chain(tab);
-            }
+            self.unsaved_changes = None;
+            self.unload_processing_chain(tab);
         }
 
         close
@@ -198,15 +420,11 @@ impl TabViewer for Tabs {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Tab {
-    /// A [`Tab`] containing the current [`Config`].
-    ///
-    /// The [`Config`] can also be updated indirectly through [`ProcessingChainTab`]s.
-    Config,
     ProcessingChain(ProcessingChainTab),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-enum ProcessingChainTab {
+pub enum ProcessingChainTab {
     /// A [`ProcessingChain`] that has not yet been saved to disk.
     New { id: Uuid },
     /// A [`ProcessingChain`] that has an associated file path.
@@ -214,23 +432,72 @@ enum ProcessingChainTab {
 }
 
 impl ProcessingChainTab {
-    fn new() -> Self {
-        Self::New { id: Uuid::new_v4() }
+    fn name(&self) -> String {
+        match self {
+            Self::New { .. } => "New".into(),
+            Self::Path(path) => path.file_name().map_or_else(
+                || path.to_string_lossy().into_owned(),
+                |file_name| file_name.to_string_lossy().into_owned(),
+            ),
+        }
     }
 }
 
-#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn processing_chain_file_dialog() -> FileDialog {
+    FileDialog::new().add_filter("Processing Chain", &["json"])
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) fn processing_chain_file_dialog() -> AsyncFileDialog {
+    AsyncFileDialog::new().add_filter("Processing Chain", &["json"])
+}
+
+/// Lets the browser download the `content` as a file.
+#[cfg(target_arch = "wasm32")]
+fn download(file_name: &str, content: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(content));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("application/json");
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    // the browser only starts the download after the click, so the url has to outlive it
+    let revoke = Closure::once_into_js(move || {
+        if let Err(error) = web_sys::Url::revoke_object_url(&url) {
+            log::warn!("failed to revoke download url: {error:?}");
+        }
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        revoke.unchecked_ref(),
+        DOWNLOAD_URL_LIFETIME_MS,
+    )?;
+    Ok(())
+}
+
+/// How long the object url of a download is kept alive after the download started.
+#[cfg(target_arch = "wasm32")]
+const DOWNLOAD_URL_LIFETIME_MS: i32 = 40_000;
+
+/// A [`ProcessingChain`] loaded from a file, alongside the last saved version.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct SavedProcessingChain {
+    #[serde(with = "processing_chain_json")]
     saved: ProcessingChain,
-    current: ProcessingChain,
+    current: ProcessingChainTable,
 }
 
 impl SavedProcessingChain {
     fn changed(&self) -> bool {
-        self.current != self.saved
+        *self.current.processing_chain() != self.saved
     }
 }
-
-fn processing_chain_file_dialog() -> FileDialog {
-    FileDialog::new().add_filter("Processing Chain", &["json"])
-}